@@ -0,0 +1,128 @@
+// Authentication - pre-shared secret challenge/response, run immediately
+// after the crypto handshake and before any other `Message` is honored.
+//
+// The server sends a random nonce as `Message::AuthChallenge`; the client
+// replies with `HMAC-SHA256(shared_secret, nonce)` hex-encoded in
+// `Message::AuthResponse`; the server recomputes the same HMAC and compares
+// in constant time before sending `Message::AuthStatus` and - only on
+// success - entering the normal message loop. See `network::handle_client`
+// and `network::connect_to_server` for where this runs.
+
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const NONCE_LEN: usize = 32;
+
+/// Per-peer-name shared secrets, loaded once at startup. `default` (from
+/// `MACWINCONTROL_SHARED_SECRET`) covers the common single-secret setup;
+/// `per_peer` lets different machines carry different keys.
+static SECRETS: Lazy<RwLock<AuthSecrets>> = Lazy::new(|| RwLock::new(AuthSecrets::load()));
+
+struct AuthSecrets {
+    default: Option<String>,
+    per_peer: HashMap<String, String>,
+}
+
+impl AuthSecrets {
+    fn load() -> Self {
+        AuthSecrets {
+            default: std::env::var("MACWINCONTROL_SHARED_SECRET").ok(),
+            per_peer: load_per_peer_secrets(),
+        }
+    }
+
+    fn secret_for(&self, peer_name: &str) -> Option<&str> {
+        self.per_peer.get(peer_name).map(String::as_str).or(self.default.as_deref())
+    }
+}
+
+fn load_per_peer_secrets() -> HashMap<String, String> {
+    let path = crate::config::auth_secrets_path();
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return HashMap::new();
+    };
+    serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+        eprintln!("⚠️ Failed to parse auth secrets at {:?}: {}", path, e);
+        HashMap::new()
+    })
+}
+
+pub fn generate_nonce() -> Vec<u8> {
+    let mut nonce = vec![0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Compute the hex HMAC response for `nonce` using the secret registered for
+/// `peer_name` (falling back to the default secret). `None` means no secret
+/// is configured at all, which callers should treat as a hard auth failure
+/// rather than silently skipping the handshake.
+pub fn respond(peer_name: &str, nonce: &[u8]) -> Option<String> {
+    let secrets = SECRETS.read().unwrap();
+    let secret = secrets.secret_for(peer_name)?;
+    Some(hmac_hex(secret, nonce))
+}
+
+/// Verify a received response in constant time. Also fails closed if no
+/// secret is configured.
+pub fn verify(peer_name: &str, nonce: &[u8], response_hex: &str) -> bool {
+    let secrets = SECRETS.read().unwrap();
+    let Some(secret) = secrets.secret_for(peer_name) else {
+        return false;
+    };
+    constant_time_eq(hmac_hex(secret, nonce).as_bytes(), response_hex.as_bytes())
+}
+
+fn hmac_hex(secret: &str, nonce: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(nonce);
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Byte-for-byte comparison that doesn't short-circuit on the first
+/// mismatch, so response-checking time doesn't leak how many leading bytes
+/// an attacker guessed correctly.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Touches the `SECRETS` `Lazy` (and the `MACWINCONTROL_SHARED_SECRET`
+    // env var it reads on first access), so everything that depends on a
+    // configured secret lives in this one test rather than being split
+    // across several - `Lazy::new` only ever runs once per process.
+    #[test]
+    fn respond_verify_round_trip_and_tamper_rejection() {
+        std::env::set_var("MACWINCONTROL_SHARED_SECRET", "test-shared-secret");
+
+        let nonce = generate_nonce();
+        let response = respond("test-peer", &nonce).expect("secret should be configured");
+        assert!(verify("test-peer", &nonce, &response));
+
+        // Flip the last hex digit - verify must reject anything but an
+        // exact match.
+        let mut tampered = response.clone();
+        let last = tampered.pop().unwrap();
+        tampered.push(if last == '0' { '1' } else { '0' });
+        assert!(!verify("test-peer", &nonce, &tampered));
+    }
+
+    #[test]
+    fn constant_time_eq_requires_exact_match() {
+        assert!(constant_time_eq(b"abc123", b"abc123"));
+        assert!(!constant_time_eq(b"abc123", b"abc124"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+}
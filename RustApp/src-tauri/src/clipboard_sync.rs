@@ -1,19 +1,33 @@
 // Clipboard module - Cross-platform clipboard access using arboard
 
-use arboard::Clipboard;
-use std::sync::Mutex;
+use arboard::{Clipboard, ImageData};
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::sync::{Mutex, RwLock};
 
 lazy_static::lazy_static! {
     static ref CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(Clipboard::new().ok());
 }
 
+/// Poll interval for the clipboard watcher, adjustable via `set_poll_interval_ms`.
+static POLL_INTERVAL_MS: Lazy<RwLock<u64>> = Lazy::new(|| RwLock::new(500));
+
+/// Hash of the last clipboard content we applied *from* the remote peer, so
+/// the watcher doesn't mistake our own write for a local change and send it
+/// straight back (only one side should be authoritative per change).
+static LAST_REMOTE_HASH: Lazy<RwLock<Option<u64>>> = Lazy::new(|| RwLock::new(None));
+
+/// Set for the duration of a remote write, so a poll that lands mid-write
+/// skips entirely instead of racing the clipboard API.
+static APPLYING_REMOTE: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+
 pub fn get_text() -> Result<String, String> {
     let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
-    
+
     if guard.is_none() {
         *guard = Clipboard::new().ok();
     }
-    
+
     match guard.as_mut() {
         Some(clipboard) => clipboard.get_text().map_err(|e| e.to_string()),
         None => Err("Clipboard not available".to_string()),
@@ -22,34 +36,164 @@ pub fn get_text() -> Result<String, String> {
 
 pub fn set_text(text: &str) -> Result<(), String> {
     let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
-    
+
     if guard.is_none() {
         *guard = Clipboard::new().ok();
     }
-    
+
     match guard.as_mut() {
         Some(clipboard) => clipboard.set_text(text.to_string()).map_err(|e| e.to_string()),
         None => Err("Clipboard not available".to_string()),
     }
 }
 
-// Watch for clipboard changes (polling-based) - will be used for clipboard sync feature
-#[allow(dead_code)]
+/// Read the clipboard image, PNG-encoded so it travels over the wire (and
+/// back to the frontend) as a compact, self-describing blob instead of a
+/// raw RGBA buffer.
+pub fn get_image() -> Result<Vec<u8>, String> {
+    let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+
+    if guard.is_none() {
+        *guard = Clipboard::new().ok();
+    }
+
+    match guard.as_mut() {
+        Some(clipboard) => {
+            let image = clipboard.get_image().map_err(|e| e.to_string())?;
+            encode_png(&image)
+        }
+        None => Err("Clipboard not available".to_string()),
+    }
+}
+
+/// Set the clipboard image from PNG bytes (as produced by `get_image`).
+pub fn set_image(png_bytes: &[u8]) -> Result<(), String> {
+    let image = decode_png(png_bytes)?;
+
+    let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+
+    if guard.is_none() {
+        *guard = Clipboard::new().ok();
+    }
+
+    match guard.as_mut() {
+        Some(clipboard) => clipboard.set_image(image).map_err(|e| e.to_string()),
+        None => Err("Clipboard not available".to_string()),
+    }
+}
+
+fn encode_png(image: &ImageData) -> Result<Vec<u8>, String> {
+    let buffer = image::RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.to_vec())
+        .ok_or_else(|| "clipboard image buffer doesn't match its own dimensions".to_string())?;
+
+    let mut png = Vec::new();
+    buffer
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+fn decode_png(png_bytes: &[u8]) -> Result<ImageData<'static>, String> {
+    let decoded = image::load_from_memory(png_bytes).map_err(|e| e.to_string())?.to_rgba8();
+    let (width, height) = decoded.dimensions();
+    Ok(ImageData {
+        width: width as usize,
+        height: height as usize,
+        bytes: Cow::Owned(decoded.into_raw()),
+    })
+}
+
+/// One clipboard snapshot, as picked up by `watch_clipboard`.
+#[derive(Clone, Debug)]
+pub enum ClipboardContent {
+    Text(String),
+    /// PNG-encoded image bytes.
+    Image(Vec<u8>),
+}
+
+fn poll_clipboard() -> Option<(ClipboardContent, u64)> {
+    if *APPLYING_REMOTE.read().unwrap() {
+        return None;
+    }
+
+    let last_remote = *LAST_REMOTE_HASH.read().unwrap();
+
+    if let Ok(text) = get_text() {
+        if !text.is_empty() {
+            let hash = hash_bytes(text.as_bytes());
+            if Some(hash) == last_remote {
+                return None;
+            }
+            return Some((ClipboardContent::Text(text), hash));
+        }
+    }
+    if let Ok(png) = get_image() {
+        let hash = hash_bytes(&png);
+        if Some(hash) == last_remote {
+            return None;
+        }
+        return Some((ClipboardContent::Image(png), hash));
+    }
+    None
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Adjust how often the watcher polls the clipboard; takes effect on its
+/// next sleep. Clamped to a sane floor so a careless caller can't busy-loop it.
+pub fn set_poll_interval_ms(ms: u64) {
+    *POLL_INTERVAL_MS.write().unwrap() = ms.max(50);
+}
+
+/// Apply clipboard content that arrived from the remote peer, guarding
+/// against the watcher seeing our own write and echoing it straight back.
+pub fn apply_remote_text(text: &str) {
+    *APPLYING_REMOTE.write().unwrap() = true;
+    let _ = set_text(text);
+    *LAST_REMOTE_HASH.write().unwrap() = Some(hash_bytes(text.as_bytes()));
+    *APPLYING_REMOTE.write().unwrap() = false;
+}
+
+pub fn apply_remote_image(png_bytes: &[u8]) {
+    *APPLYING_REMOTE.write().unwrap() = true;
+    let _ = set_image(png_bytes);
+    *LAST_REMOTE_HASH.write().unwrap() = Some(hash_bytes(png_bytes));
+    *APPLYING_REMOTE.write().unwrap() = false;
+}
+
+// Watch for clipboard changes (polling-based), wired into the network layer
+// by `network::start_clipboard_sync`.
 pub fn watch_clipboard<F>(mut callback: F)
 where
-    F: FnMut(String) + Send + 'static,
+    F: FnMut(ClipboardContent) + Send + 'static,
 {
     std::thread::spawn(move || {
-        let mut last_text = String::new();
-        
+        let mut last_hash: Option<u64> = None;
+        let mut last_seq: Option<u64> = None;
+
         loop {
-            if let Ok(text) = get_text() {
-                if text != last_text {
-                    last_text = text.clone();
-                    callback(text);
+            // `clipboard_sequence` is 0 on targets that don't expose one (see
+            // the fallback `platform` impl), so only use it to skip a poll
+            // when it's a real, moving counter.
+            let seq = crate::input::clipboard_sequence();
+            let seq_unchanged = seq != 0 && last_seq == Some(seq);
+            last_seq = Some(seq);
+
+            if !seq_unchanged {
+                if let Some((content, hash)) = poll_clipboard() {
+                    if last_hash != Some(hash) {
+                        last_hash = Some(hash);
+                        callback(content);
+                    }
                 }
             }
-            std::thread::sleep(std::time::Duration::from_millis(500));
+            let interval = *POLL_INTERVAL_MS.read().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(interval));
         }
     });
 }
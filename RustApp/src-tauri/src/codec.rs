@@ -0,0 +1,182 @@
+// Wire codec - length-delimited framing for `network::Message`
+//
+// Frame layout: [4-byte BE length][1 flag byte][payload]
+//   length  = 1 (flag byte) + payload.len()
+//   flag    = bit 0 set when the inner bytes are zstd-compressed
+//             bit 1 set when the payload is sealed (12-byte nonce || ciphertext)
+//   payload = inner bytes, optionally sealed by an AEAD cipher
+//   inner   = bincode-encoded `Message`, optionally zstd-compressed
+//
+// Small, high-frequency messages (mouse moves) stay uncompressed since zstd
+// framing overhead would dwarf the payload; larger ones (clipboard text,
+// layout JSON) cross COMPRESS_THRESHOLD and get compressed automatically.
+//
+// Encryption (see `crypto`) is applied as the outermost layer, after
+// compression, using a cipher fixed for the lifetime of one connection's
+// read or write half - see `MessageCodec::encrypted`.
+
+use bytes::{Buf, BufMut, BytesMut};
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::network::Message;
+
+/// Payloads at or above this size are zstd-compressed before framing.
+const COMPRESS_THRESHOLD: usize = 1024;
+const COMPRESS_FLAG: u8 = 0b0000_0001;
+const ENCRYPTED_FLAG: u8 = 0b0000_0010;
+
+/// Default upper bound on a single frame's payload length, to avoid ever
+/// trying to allocate an unbounded buffer for a corrupt or hostile length
+/// prefix. Override per-codec with `MessageCodec::with_max_frame` (e.g. a
+/// transport that's expected to carry large screen-stream frames).
+const DEFAULT_MAX_FRAME_LEN: usize = 1024 * 1024;
+
+/// A single direction's AEAD state: one cipher, fixed for the connection,
+/// and a strictly-incrementing nonce counter (safe because each direction
+/// of a connection uses its own key - see `crypto::handshake`).
+struct Cipher {
+    cipher: ChaCha20Poly1305,
+    counter: u64,
+}
+
+impl Cipher {
+    fn next_nonce(&mut self) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..8].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter += 1;
+        nonce
+    }
+}
+
+pub struct MessageCodec {
+    cipher: Option<Cipher>,
+    max_frame_len: usize,
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        MessageCodec { cipher: None, max_frame_len: DEFAULT_MAX_FRAME_LEN }
+    }
+}
+
+impl MessageCodec {
+    /// No encryption - used before a handshake completes, or for transports
+    /// that already provide confidentiality.
+    pub fn plain() -> Self {
+        MessageCodec::default()
+    }
+
+    /// Encrypt/decrypt every frame through `cipher`. Pass the send-direction
+    /// cipher from `crypto::handshake` to a write-half codec, and the
+    /// recv-direction cipher to a read-half codec.
+    pub fn encrypted(cipher: ChaCha20Poly1305) -> Self {
+        MessageCodec { cipher: Some(Cipher { cipher, counter: 0 }), ..MessageCodec::default() }
+    }
+
+    /// Override the default frame-size cap, e.g. for a transport expected to
+    /// carry larger payloads than ordinary input/clipboard traffic.
+    pub fn with_max_frame(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Item = Message;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>, Self::Error> {
+        if src.len() < 4 {
+            return Ok(None);
+        }
+
+        let len = u32::from_be_bytes(src[..4].try_into().unwrap()) as usize;
+        if len == 0 || len > self.max_frame_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("frame length {} outside allowed range", len),
+            ));
+        }
+
+        if src.len() < 4 + len {
+            src.reserve(4 + len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(4);
+        let flag = src.get_u8();
+        let payload = src.split_to(len - 1);
+
+        let inner = if flag & ENCRYPTED_FLAG != 0 {
+            let Some(state) = self.cipher.as_ref() else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "received encrypted frame on an unencrypted connection",
+                ));
+            };
+            if payload.len() < 12 {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "sealed frame too short"));
+            }
+            let (nonce_bytes, ciphertext) = payload.split_at(12);
+            state
+                .cipher
+                .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+                .map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "decryption failed (wrong key or tampered frame)")
+                })?
+        } else {
+            payload.to_vec()
+        };
+
+        let bytes = if flag & COMPRESS_FLAG != 0 {
+            zstd::stream::decode_all(&inner[..])
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            inner
+        };
+
+        bincode::deserialize(&bytes)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Encoder<Message> for MessageCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, msg: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let raw = bincode::serialize(&msg)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let mut flag = 0u8;
+        let inner = if raw.len() >= COMPRESS_THRESHOLD {
+            flag |= COMPRESS_FLAG;
+            zstd::stream::encode_all(&raw[..], 0)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            raw
+        };
+
+        let payload = if let Some(state) = self.cipher.as_mut() {
+            flag |= ENCRYPTED_FLAG;
+            let nonce_bytes = state.next_nonce();
+            let ciphertext = state
+                .cipher
+                .encrypt(Nonce::from_slice(&nonce_bytes), inner.as_slice())
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "encryption failed"))?;
+            let mut sealed = nonce_bytes.to_vec();
+            sealed.extend_from_slice(&ciphertext);
+            sealed
+        } else {
+            inner
+        };
+
+        dst.reserve(4 + 1 + payload.len());
+        dst.put_u32((1 + payload.len()) as u32);
+        dst.put_u8(flag);
+        dst.put_slice(&payload);
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+// Config module - persist computers, clipboard sync, and screen layout
+// across restarts as YAML under the platform config dir.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::ComputerInfo;
+
+/// Bump this whenever `PersistedConfig`'s shape changes, and add a migration
+/// step in `load()` rather than breaking old config files.
+pub const CURRENT_FORMAT_VERSION: u32 = 2;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PersistedConfig {
+    pub format_version: u32,
+    pub computers: Vec<ComputerInfo>,
+    pub clipboard_sync_enabled: bool,
+    pub screen_layout: String,
+    /// "tcp" or "quic" - see `transport::TransportKind`. Stored as a plain
+    /// string (rather than the enum directly) so an old config missing the
+    /// field just falls back to serde's default instead of failing to parse.
+    #[serde(default = "default_transport")]
+    pub transport: String,
+}
+
+fn default_transport() -> String {
+    "tcp".to_string()
+}
+
+impl Default for PersistedConfig {
+    fn default() -> Self {
+        PersistedConfig {
+            format_version: CURRENT_FORMAT_VERSION,
+            computers: Vec::new(),
+            clipboard_sync_enabled: true,
+            screen_layout: "right".to_string(),
+            transport: default_transport(),
+        }
+    }
+}
+
+fn config_dir() -> PathBuf {
+    let dir = dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("MacWinControl");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+fn config_path() -> PathBuf {
+    config_dir().join("config.yaml")
+}
+
+/// Path to the optional per-peer-name shared-secret map used by `auth`, e.g.:
+///   my-windows-pc: "a long random secret"
+///   office-mac: "a different long random secret"
+/// Kept separate from `config.yaml` since it's read-only to the app (never
+/// rewritten by `save`) and shouldn't round-trip through `export_config`.
+pub fn auth_secrets_path() -> PathBuf {
+    config_dir().join("auth_secrets.yaml")
+}
+
+/// Load the persisted config, migrating old `format_version`s in place.
+/// Falls back to `PersistedConfig::default()` if there's no file yet or it
+/// fails to parse - a corrupt config shouldn't stop the app from starting.
+pub fn load() -> PersistedConfig {
+    let path = config_path();
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return PersistedConfig::default();
+    };
+
+    match serde_yaml::from_str::<PersistedConfig>(&contents) {
+        Ok(mut config) => {
+            if config.format_version < CURRENT_FORMAT_VERSION {
+                println!(
+                    "⚙️ Migrating config at {:?} from format_version {} to {}",
+                    path, config.format_version, CURRENT_FORMAT_VERSION
+                );
+                config.format_version = CURRENT_FORMAT_VERSION;
+            }
+            config
+        }
+        Err(e) => {
+            eprintln!("⚠️ Failed to parse config at {:?}: {}", path, e);
+            PersistedConfig::default()
+        }
+    }
+}
+
+pub fn save(config: &PersistedConfig) {
+    let path = config_path();
+    match serde_yaml::to_string(config) {
+        Ok(yaml) => {
+            if let Err(e) = std::fs::write(&path, yaml) {
+                eprintln!("⚠️ Failed to write config to {:?}: {}", path, e);
+            }
+        }
+        Err(e) => eprintln!("⚠️ Failed to serialize config: {}", e),
+    }
+}
+
+/// Serialize a config to a YAML string, for `export_config`.
+pub fn export_to_string(config: &PersistedConfig) -> Result<String, String> {
+    serde_yaml::to_string(config).map_err(|e| e.to_string())
+}
+
+/// Parse a YAML string produced by `export_to_string` (from this machine or
+/// another one), for `import_config`.
+pub fn import_from_string(yaml: &str) -> Result<PersistedConfig, String> {
+    serde_yaml::from_str(yaml).map_err(|e| e.to_string())
+}
@@ -0,0 +1,91 @@
+// Crypto module - end-to-end encryption for peer connections
+//
+// Immediately after the TCP connection is established (before any `Message`
+// is framed), both sides run a tiny handshake: generate an ephemeral X25519
+// keypair, exchange public keys, and derive a shared secret via ECDH. An
+// optional pairing code that the user enters on both machines is mixed into
+// the HKDF so a man-in-the-middle sitting on the (unauthenticated) discovery
+// channel can't just complete the ECDH itself - a mismatched code silently
+// yields different keys on each side, and every subsequent decrypt fails.
+//
+// Two keys are derived, one per direction (same idea as TLS 1.3 traffic
+// keys), so independently-incrementing nonce counters on each side can never
+// collide under the same key.
+
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::aead::KeyInit;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+const INITIATOR_TO_RESPONDER: &[u8] = b"macwinctrl-initiator-to-responder";
+const RESPONDER_TO_INITIATOR: &[u8] = b"macwinctrl-responder-to-initiator";
+const FINGERPRINT_INFO: &[u8] = b"macwinctrl-fingerprint";
+
+/// Short fingerprint of the negotiated session key, for out-of-band peer
+/// verification (shown in the UI alongside the connection status).
+pub type Fingerprint = [u8; 16];
+
+pub struct HandshakeOutcome {
+    pub send_cipher: ChaCha20Poly1305,
+    pub recv_cipher: ChaCha20Poly1305,
+    pub fingerprint: Fingerprint,
+}
+
+/// Run the X25519 handshake over `stream` and derive per-direction session
+/// keys. `is_initiator` must agree with which side dials out (`connect_to_server`)
+/// vs. accepts (`handle_client`) so both ends pick matching send/recv keys.
+/// `pairing_code`, when set, must match on both ends or the handshake
+/// completes but produces a session neither side can actually decrypt.
+pub async fn handshake<S>(
+    stream: &mut S,
+    is_initiator: bool,
+    pairing_code: Option<&str>,
+) -> std::io::Result<HandshakeOutcome>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let secret = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+    let public = PublicKey::from(&secret);
+
+    stream.write_all(public.as_bytes()).await?;
+    stream.flush().await?;
+
+    let mut peer_bytes = [0u8; 32];
+    stream.read_exact(&mut peer_bytes).await?;
+    let peer_public = PublicKey::from(peer_bytes);
+
+    let shared = secret.diffie_hellman(&peer_public);
+
+    let hk = Hkdf::<Sha256>::new(pairing_code.map(str::as_bytes), shared.as_bytes());
+
+    let mut initiator_key = [0u8; 32];
+    let mut responder_key = [0u8; 32];
+    let mut fingerprint = [0u8; 16];
+    hk.expand(INITIATOR_TO_RESPONDER, &mut initiator_key)
+        .map_err(hkdf_err)?;
+    hk.expand(RESPONDER_TO_INITIATOR, &mut responder_key)
+        .map_err(hkdf_err)?;
+    hk.expand(FINGERPRINT_INFO, &mut fingerprint).map_err(hkdf_err)?;
+
+    let (send_key, recv_key) = if is_initiator {
+        (initiator_key, responder_key)
+    } else {
+        (responder_key, initiator_key)
+    };
+
+    Ok(HandshakeOutcome {
+        send_cipher: ChaCha20Poly1305::new((&send_key).into()),
+        recv_cipher: ChaCha20Poly1305::new((&recv_key).into()),
+        fingerprint,
+    })
+}
+
+fn hkdf_err(_: hkdf::InvalidLength) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, "HKDF expand failed")
+}
+
+pub fn fingerprint_hex(fingerprint: &Fingerprint) -> String {
+    fingerprint.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -13,13 +13,42 @@ pub struct ScreenInfo {
     pub is_primary: bool,
 }
 
+/// One local event swallowed by `start_capture` while we're acting as the
+/// controller, for `network::start_input_capture` to forward to the remote
+/// instead of letting it reach a local app. Mouse motion isn't included -
+/// `network::start_mouse_tracking`'s edge-follow polling already tracks
+/// that by position delta once a tap/hook is installed.
+#[derive(Clone, Debug)]
+pub enum InputEvent {
+    MouseButton { button: String, action: String },
+    Scroll { delta_x: i32, delta_y: i32 },
+    Key { key_code: u32, action: String },
+}
+
+/// Cursor shape the controller can ask this machine to mirror, so the local
+/// pointer reflects what's under the remote one (I-beam over text, resize
+/// arrows over a border, a hand over a link) instead of a plain arrow the
+/// whole time control is handed off. Pairs with `hide_cursor`/`show_cursor` -
+/// a caller can hide the local cursor entirely or mirror the remote shape,
+/// whichever feels right for that session.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum CursorShape {
+    Arrow,
+    IBeam,
+    Hand,
+    Crosshair,
+    ResizeNS,
+    ResizeEW,
+    NotAllowed,
+    Wait,
+}
+
 // ============= macOS Implementation =============
 #[cfg(target_os = "macos")]
 mod platform {
     use super::ScreenInfo;
-    use core_graphics::event::{CGEvent, CGEventType, CGMouseButton, CGEventTapLocation};
+    use core_graphics::event::{CGEvent, ScrollEventUnit};
     use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
-    use core_graphics::geometry::CGPoint;
     use core_graphics::display::{CGDisplay, CGGetActiveDisplayList};
 
     // Import for cursor hiding
@@ -101,64 +130,247 @@ mod platform {
         (0, 0)
     }
 
-    pub fn move_mouse(x: i32, y: i32) {
-        let point = CGPoint::new(x as f64, y as f64);
-        if let Ok(source) = CGEventSource::new(CGEventSourceStateID::HIDSystemState) {
-            if let Ok(event) = CGEvent::new_mouse_event(
-                source,
-                CGEventType::MouseMoved,
-                point,
-                CGMouseButton::Left,
-            ) {
-                event.post(CGEventTapLocation::HID);
+    /// A single `CGEventCreateScrollWheelEvent2` only has room for a
+    /// plausible wheel delta before it starts clipping, so a big remote
+    /// scroll (a fast trackpad flick, or many batched notches) gets split
+    /// into several events posted back to back instead of one event the
+    /// OS might clamp or ignore outright.
+    const MAX_SCROLL_PER_EVENT: i32 = 100;
+
+    /// Post a scroll wheel event via `CGEventCreateScrollWheelEvent2`.
+    /// `precise` picks pixel units (what trackpad momentum scrolling sends)
+    /// over line units (what a notched mouse wheel sends) - mixing the two
+    /// up makes momentum scrolls feel either frozen or wildly overshooting.
+    /// `delta_y`/`delta_x` follow the same sign convention as the Windows
+    /// `MOUSEEVENTF_WHEEL`/`MOUSEEVENTF_HWHEEL` path: positive `delta_y`
+    /// scrolls content up (wheel away from the user).
+    pub fn post_scroll_event(delta_x: i32, delta_y: i32, precise: bool) {
+        let unit = if precise { ScrollEventUnit::PIXEL } else { ScrollEventUnit::LINE };
+        for (step_x, step_y) in split_into_steps(delta_x, delta_y) {
+            let Ok(source) = CGEventSource::new(CGEventSourceStateID::HIDSystemState) else { continue };
+            let Ok(event) = CGEvent::new_scroll_event(source, unit, 2, step_y, step_x, 0) else { continue };
+            event.post(core_graphics::event::CGEventTapLocation::HID);
+        }
+    }
+
+    /// Split a (delta_x, delta_y) pair into a sequence of steps, each no
+    /// larger than `MAX_SCROLL_PER_EVENT` per axis.
+    fn split_into_steps(delta_x: i32, delta_y: i32) -> Vec<(i32, i32)> {
+        let steps = ((delta_x.abs().max(delta_y.abs())) / MAX_SCROLL_PER_EVENT + 1).max(1);
+        let mut remaining = (delta_x, delta_y);
+        let mut out = Vec::with_capacity(steps as usize);
+        for i in 0..steps {
+            if i == steps - 1 {
+                out.push(remaining);
             } else {
-                println!("⚠️ [macOS] Failed to create mouse move event for ({}, {})", x, y);
+                let step = (remaining.0 / (steps - i), remaining.1 / (steps - i));
+                out.push(step);
+                remaining = (remaining.0 - step.0, remaining.1 - step.1);
             }
-        } else {
-            println!("⚠️ [macOS] Failed to create CGEventSource for mouse move");
         }
+        out
     }
 
-    pub fn mouse_click(button: &str, action: &str) {
-        let source = match CGEventSource::new(CGEventSourceStateID::HIDSystemState) {
-            Ok(s) => s,
-            Err(_) => return,
-        };
-        
-        let (event_type, mouse_button) = match (button, action) {
-            ("left", "press") => (CGEventType::LeftMouseDown, CGMouseButton::Left),
-            ("left", "release") => (CGEventType::LeftMouseUp, CGMouseButton::Left),
-            ("right", "press") => (CGEventType::RightMouseDown, CGMouseButton::Right),
-            ("right", "release") => (CGEventType::RightMouseUp, CGMouseButton::Right),
-            ("middle", "press") => (CGEventType::OtherMouseDown, CGMouseButton::Center),
-            ("middle", "release") => (CGEventType::OtherMouseUp, CGMouseButton::Center),
-            _ => return,
-        };
-        
-        let (x, y) = get_mouse_position();
-        let point = CGPoint::new(x as f64, y as f64);
-        
-        if let Ok(event) = CGEvent::new_mouse_event(source, event_type, point, mouse_button) {
-            event.post(CGEventTapLocation::HID);
+    /// Type arbitrary Unicode text by posting keyboard events carrying the
+    /// UTF-16 string directly (`CGEventKeyboardSetUnicodeString`), instead
+    /// of translating each character to a virtual keycode first - the only
+    /// way to send a character the local keyboard layout has no key for
+    /// (accented letters, emoji, non-US layouts).
+    pub fn type_text(text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        for key_down in [true, false] {
+            let Ok(source) = CGEventSource::new(CGEventSourceStateID::HIDSystemState) else { continue };
+            let Ok(event) = CGEvent::new_keyboard_event(source, 0, key_down) else { continue };
+            // Sets the event's UniChar buffer (`CGEventKeyboardSetUnicodeString`
+            // under the hood) with a keycode of 0, so the OS types exactly
+            // these code units instead of reinterpreting a virtual key.
+            event.set_string(text);
+            event.post(core_graphics::event::CGEventTapLocation::HID);
+        }
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const std::os::raw::c_char) -> *mut std::ffi::c_void;
+        fn sel_registerName(name: *const std::os::raw::c_char) -> *mut std::ffi::c_void;
+    }
+
+    /// `NSPasteboard.generalPasteboard.changeCount`, bumped by the OS every
+    /// time *anything* writes the clipboard (us or another app). Cheap
+    /// enough to poll every tick so `clipboard_sync` only re-hashes the full
+    /// contents when this has actually moved.
+    pub fn clipboard_sequence() -> u64 {
+        unsafe {
+            // objc_msgSend is declared per call site with the signature that
+            // matches its return type, since it's really a varargs-style C
+            // function whose ABI depends on what's being sent back.
+            let get_pasteboard: extern "C" fn(*mut std::ffi::c_void, *mut std::ffi::c_void) -> *mut std::ffi::c_void =
+                std::mem::transmute(get_msg_send());
+            let get_change_count: extern "C" fn(*mut std::ffi::c_void, *mut std::ffi::c_void) -> i64 =
+                std::mem::transmute(get_msg_send());
+
+            let class = objc_getClass(b"NSPasteboard\0".as_ptr() as *const _);
+            let general_pasteboard_sel = sel_registerName(b"generalPasteboard\0".as_ptr() as *const _);
+            let pasteboard = get_pasteboard(class, general_pasteboard_sel);
+
+            let change_count_sel = sel_registerName(b"changeCount\0".as_ptr() as *const _);
+            get_change_count(pasteboard, change_count_sel) as u64
         }
     }
 
-    pub fn key_event(key_code: u32, action: &str) {
-        let source = match CGEventSource::new(CGEventSourceStateID::HIDSystemState) {
-            Ok(s) => s,
-            Err(_) => return,
+    #[link(name = "objc")]
+    extern "C" {
+        #[link_name = "objc_msgSend"]
+        fn objc_msg_send_raw();
+    }
+
+    fn get_msg_send() -> unsafe extern "C" fn() {
+        objc_msg_send_raw
+    }
+
+    /// Set the local cursor to mirror what's under the remote pointer, via
+    /// `[[NSCursor <classMethod>] set]`. Every shape maps to a real
+    /// `NSCursor` class method except `Wait` - Cocoa doesn't expose one, so
+    /// it falls back to the arrow like the rest of the unmapped cases would.
+    pub fn set_cursor(shape: super::CursorShape) {
+        let selector: &[u8] = match shape {
+            super::CursorShape::Arrow => b"arrowCursor\0",
+            super::CursorShape::IBeam => b"IBeamCursor\0",
+            super::CursorShape::Hand => b"pointingHandCursor\0",
+            super::CursorShape::Crosshair => b"crosshairCursor\0",
+            super::CursorShape::ResizeNS => b"resizeUpDownCursor\0",
+            super::CursorShape::ResizeEW => b"resizeLeftRightCursor\0",
+            super::CursorShape::NotAllowed => b"operationNotAllowedCursor\0",
+            super::CursorShape::Wait => b"arrowCursor\0",
         };
-        
-        let keydown = action == "press";
-        
-        if let Ok(event) = CGEvent::new_keyboard_event(source, key_code as u16, keydown) {
-            event.post(CGEventTapLocation::HID);
+
+        unsafe {
+            let get_cursor: extern "C" fn(*mut std::ffi::c_void, *mut std::ffi::c_void) -> *mut std::ffi::c_void =
+                std::mem::transmute(get_msg_send());
+            let send_set: extern "C" fn(*mut std::ffi::c_void, *mut std::ffi::c_void) =
+                std::mem::transmute(get_msg_send());
+
+            let class = objc_getClass(b"NSCursor\0".as_ptr() as *const _);
+            let cursor_sel = sel_registerName(selector.as_ptr() as *const _);
+            let cursor = get_cursor(class, cursor_sel);
+
+            let set_sel = sel_registerName(b"set\0".as_ptr() as *const _);
+            send_set(cursor, set_sel);
+        }
+    }
+
+    use core_graphics::event::{
+        CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType, EventField,
+    };
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    /// Guards against `start_capture` installing a second tap if called
+    /// again before a matching `stop_capture` - the run loop it spawns only
+    /// ever tears down from the thread that owns it.
+    static CAPTURE_ACTIVE: AtomicBool = AtomicBool::new(false);
+    static CAPTURE_RUN_LOOP: Mutex<Option<CFRunLoop>> = Mutex::new(None);
+
+    /// Install a `kCGHIDEventTap` that swallows local mouse/keyboard input
+    /// and hands each event to `callback` instead of letting it reach the
+    /// focused app - the "am I the controller" half of the edge-follow
+    /// handoff in `network::check_edge_transition`. Runs on its own thread
+    /// with its own `CFRunLoop`, since a tap has to be serviced by the
+    /// run loop of the thread that created it.
+    pub fn start_capture<F>(callback: F)
+    where
+        F: Fn(super::InputEvent) + Send + 'static,
+    {
+        if CAPTURE_ACTIVE.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        std::thread::spawn(move || {
+            let events = vec![
+                CGEventType::LeftMouseDown,
+                CGEventType::LeftMouseUp,
+                CGEventType::RightMouseDown,
+                CGEventType::RightMouseUp,
+                CGEventType::ScrollWheel,
+                CGEventType::KeyDown,
+                CGEventType::KeyUp,
+            ];
+
+            let tap = CGEventTap::new(
+                CGEventTapLocation::HID,
+                CGEventTapPlacement::HeadInsertEventTap,
+                CGEventTapOptions::Default,
+                events,
+                move |_proxy, event_type, event| {
+                    if let Some(input_event) = translate_event(event_type, &event) {
+                        callback(input_event);
+                    }
+                    // Returning `None` drops the event instead of passing it
+                    // on to whatever app has focus locally.
+                    None
+                },
+            );
+
+            match tap {
+                Ok(tap) => {
+                    let run_loop = CFRunLoop::get_current();
+                    unsafe {
+                        run_loop.add_source(&tap.mach_port.create_runloop_source(0), kCFRunLoopCommonModes);
+                    }
+                    tap.enable();
+                    *CAPTURE_RUN_LOOP.lock().unwrap() = Some(run_loop);
+                    println!("🎯 Input capture tap installed");
+                    CFRunLoop::run_current();
+                    println!("🎯 Input capture run loop exited");
+                }
+                Err(_) => {
+                    println!("⚠️ Couldn't create CGEventTap - grant Accessibility/Input Monitoring permission?");
+                }
+            }
+
+            CAPTURE_ACTIVE.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Stop the tap installed by `start_capture`, letting local input reach
+    /// apps again.
+    pub fn stop_capture() {
+        if let Some(run_loop) = CAPTURE_RUN_LOOP.lock().unwrap().take() {
+            run_loop.stop();
         }
+        println!("🎯 Input capture tap removed");
     }
 
-    #[allow(dead_code)]
-    pub fn scroll(_delta_x: i32, _delta_y: i32) {
-        // Scroll not implemented yet
+    fn translate_event(event_type: CGEventType, event: &core_graphics::event::CGEvent) -> Option<super::InputEvent> {
+        let down_up = |action: &str, button: &str| {
+            Some(super::InputEvent::MouseButton { button: button.to_string(), action: action.to_string() })
+        };
+        match event_type {
+            CGEventType::LeftMouseDown => down_up("down", "left"),
+            CGEventType::LeftMouseUp => down_up("up", "left"),
+            CGEventType::RightMouseDown => down_up("down", "right"),
+            CGEventType::RightMouseUp => down_up("up", "right"),
+            // The delta-axis fields count whole lines; scale them to the
+            // 120-per-notch units `network::Message::Scroll` uses.
+            CGEventType::ScrollWheel => Some(super::InputEvent::Scroll {
+                delta_x: event.get_integer_value_field(EventField::SCROLL_WHEEL_EVENT_DELTA_AXIS_2) as i32
+                    * super::TICKS_PER_NOTCH,
+                delta_y: event.get_integer_value_field(EventField::SCROLL_WHEEL_EVENT_DELTA_AXIS_1) as i32
+                    * super::TICKS_PER_NOTCH,
+            }),
+            CGEventType::KeyDown => Some(super::InputEvent::Key {
+                key_code: event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u32,
+                action: "down".to_string(),
+            }),
+            CGEventType::KeyUp => Some(super::InputEvent::Key {
+                key_code: event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u32,
+                action: "up".to_string(),
+            }),
+            _ => None,
+        }
     }
 }
 
@@ -166,13 +378,19 @@ mod platform {
 #[cfg(target_os = "windows")]
 mod platform {
     use super::ScreenInfo;
-    use windows::Win32::UI::Input::KeyboardAndMouse::*;
     use windows::Win32::UI::WindowsAndMessaging::{
         GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN,
-        ShowCursor, GetCursorPos, SetCursorPos,
+        ShowCursor, GetCursorPos,
+        SetWindowsHookExW, UnhookWindowsHookEx, CallNextHookEx, GetMessageW, PostThreadMessageW,
+        WH_MOUSE_LL, WH_KEYBOARD_LL, KBDLLHOOKSTRUCT, MSLLHOOKSTRUCT, MSG, HHOOK,
+        WM_LBUTTONDOWN, WM_LBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_MOUSEWHEEL, WM_KEYDOWN, WM_KEYUP, WM_QUIT,
     };
-    use windows::Win32::Foundation::{POINT, RECT, BOOL, LPARAM};
+    use windows::Win32::Foundation::{POINT, RECT, BOOL, LPARAM, WPARAM, LRESULT};
     use windows::Win32::Graphics::Gdi::*;
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use once_cell::sync::Lazy;
+    use std::sync::Mutex;
 
     // MONITORINFOF_PRIMARY constant (may not be exported in newer windows crate versions)
     const MONITORINFOF_PRIMARY: u32 = 0x00000001;
@@ -267,106 +485,45 @@ mod platform {
         }
     }
 
-    pub fn move_mouse(x: i32, y: i32) {
-        unsafe {
-            if let Err(e) = SetCursorPos(x, y) {
-                // Log error if move fails
-                println!("⚠️ SetCursorPos({}, {}) failed: {:?}", x, y, e);
-            }
+    /// Type arbitrary Unicode text with `SendInput`/`KEYEVENTF_UNICODE`
+    /// instead of translating each character to a virtual keycode first -
+    /// the only way to send a character the local keyboard layout has no
+    /// key for (accented letters, emoji, non-US layouts). A character
+    /// outside the BMP encodes to a UTF-16 surrogate pair; each surrogate
+    /// is sent as its own down/up pair, same as a real Unicode IME does.
+    pub fn type_text(text: &str) {
+        for code_unit in text.encode_utf16() {
+            send_unicode_key(code_unit, false);
+            send_unicode_key(code_unit, true);
         }
     }
 
-    pub fn mouse_click(button: &str, action: &str) {
-        let flags = match (button, action) {
-            ("left", "press") => MOUSEEVENTF_LEFTDOWN,
-            ("left", "release") => MOUSEEVENTF_LEFTUP,
-            ("right", "press") => MOUSEEVENTF_RIGHTDOWN,
-            ("right", "release") => MOUSEEVENTF_RIGHTUP,
-            ("middle", "press") => MOUSEEVENTF_MIDDLEDOWN,
-            ("middle", "release") => MOUSEEVENTF_MIDDLEUP,
-            _ => return,
-        };
-        
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dx: 0,
-                    dy: 0,
-                    mouseData: 0,
-                    dwFlags: flags,
-                    time: 0,
-                    dwExtraInfo: 0,
-                },
-            },
+    fn send_unicode_key(code_unit: u16, key_up: bool) {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY,
         };
-        
-        unsafe {
-            SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-        }
-    }
 
-    pub fn key_event(key_code: u32, action: &str) {
-        let flags = if action == "release" {
-            KEYEVENTF_KEYUP
-        } else {
-            KEYBD_EVENT_FLAGS(0)
-        };
-        
+        let mut flags = KEYEVENTF_UNICODE;
+        if key_up {
+            flags |= KEYEVENTF_KEYUP;
+        }
         let input = INPUT {
             r#type: INPUT_KEYBOARD,
             Anonymous: INPUT_0 {
                 ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(key_code as u16),
-                    wScan: 0,
+                    wVk: VIRTUAL_KEY(0),
+                    wScan: code_unit,
                     dwFlags: flags,
                     time: 0,
                     dwExtraInfo: 0,
                 },
             },
         };
-        
         unsafe {
             SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
         }
     }
 
-    pub fn scroll(delta_x: i32, delta_y: i32) {
-        if delta_y != 0 {
-            let input = INPUT {
-                r#type: INPUT_MOUSE,
-                Anonymous: INPUT_0 {
-                    mi: MOUSEINPUT {
-                        dx: 0,
-                        dy: 0,
-                        mouseData: (delta_y * 120) as u32,
-                        dwFlags: MOUSEEVENTF_WHEEL,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            };
-            unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32); }
-        }
-        
-        if delta_x != 0 {
-            let input = INPUT {
-                r#type: INPUT_MOUSE,
-                Anonymous: INPUT_0 {
-                    mi: MOUSEINPUT {
-                        dx: 0,
-                        dy: 0,
-                        mouseData: (delta_x * 120) as u32,
-                        dwFlags: MOUSEEVENTF_HWHEEL,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    },
-                },
-            };
-            unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32); }
-        }
-    }
-
     pub fn hide_cursor() {
         unsafe {
             // ShowCursor decrements counter, cursor hidden when < 0
@@ -384,6 +541,137 @@ mod platform {
         }
         println!("👁️ Windows cursor shown");
     }
+
+    /// Set the local cursor to mirror what's under the remote pointer, via
+    /// `LoadCursorW`/`SetCursor`. Every shape maps to a real system cursor
+    /// except `Wait` which has no single-arrow IDC equivalent for a
+    /// momentary mirror, so it falls back to the arrow like baseview does
+    /// for cursors it doesn't recognize.
+    pub fn set_cursor(shape: super::CursorShape) {
+        use windows::Win32::UI::WindowsAndMessaging::{
+            LoadCursorW, SetCursor, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_IBEAM, IDC_NO, IDC_SIZENS, IDC_SIZEWE,
+        };
+
+        let id = match shape {
+            super::CursorShape::Arrow => IDC_ARROW,
+            super::CursorShape::IBeam => IDC_IBEAM,
+            super::CursorShape::Hand => IDC_HAND,
+            super::CursorShape::Crosshair => IDC_CROSS,
+            super::CursorShape::ResizeNS => IDC_SIZENS,
+            super::CursorShape::ResizeEW => IDC_SIZEWE,
+            super::CursorShape::NotAllowed => IDC_NO,
+            super::CursorShape::Wait => IDC_ARROW,
+        };
+
+        unsafe {
+            if let Ok(cursor) = LoadCursorW(None, id) {
+                SetCursor(cursor);
+            }
+        }
+    }
+
+    /// Bumped by the OS every time any app writes the clipboard, so
+    /// `clipboard_sync` can skip re-hashing the contents when nothing changed.
+    pub fn clipboard_sequence() -> u64 {
+        unsafe { GetClipboardSequenceNumber() as u64 }
+    }
+
+    static CAPTURE_CALLBACK: Lazy<Mutex<Option<Box<dyn Fn(super::InputEvent) + Send>>>> =
+        Lazy::new(|| Mutex::new(None));
+    static MOUSE_HOOK: Mutex<Option<isize>> = Mutex::new(None);
+    static KEYBOARD_HOOK: Mutex<Option<isize>> = Mutex::new(None);
+    /// Thread ID of the message-pump thread `start_capture` spawns, so
+    /// `stop_capture` can post it a `WM_QUIT` to unblock `GetMessageW`.
+    static CAPTURE_THREAD_ID: Mutex<Option<u32>> = Mutex::new(None);
+
+    unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let data = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            let event = match wparam.0 as u32 {
+                WM_LBUTTONDOWN => Some(super::InputEvent::MouseButton { button: "left".to_string(), action: "down".to_string() }),
+                WM_LBUTTONUP => Some(super::InputEvent::MouseButton { button: "left".to_string(), action: "up".to_string() }),
+                WM_RBUTTONDOWN => Some(super::InputEvent::MouseButton { button: "right".to_string(), action: "down".to_string() }),
+                WM_RBUTTONUP => Some(super::InputEvent::MouseButton { button: "right".to_string(), action: "up".to_string() }),
+                WM_MOUSEWHEEL => {
+                    // High word of mouseData is a signed wheel delta, in the
+                    // same 120-per-notch units `network::Message::Scroll` uses.
+                    let wheel_delta = ((data.mouseData >> 16) as i16) as i32;
+                    Some(super::InputEvent::Scroll { delta_x: 0, delta_y: wheel_delta })
+                }
+                _ => None,
+            };
+            if let Some(event) = event {
+                if let Some(cb) = CAPTURE_CALLBACK.lock().unwrap().as_ref() {
+                    cb(event);
+                }
+                return LRESULT(1); // non-zero blocks the event from propagating
+            }
+        }
+        CallNextHookEx(None, code, wparam, lparam)
+    }
+
+    unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let action = match wparam.0 as u32 {
+                WM_KEYDOWN => "down",
+                WM_KEYUP => "up",
+                _ => return CallNextHookEx(None, code, wparam, lparam),
+            };
+            let data = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            if let Some(cb) = CAPTURE_CALLBACK.lock().unwrap().as_ref() {
+                cb(super::InputEvent::Key { key_code: data.vkCode, action: action.to_string() });
+            }
+            return LRESULT(1);
+        }
+        CallNextHookEx(None, code, wparam, lparam)
+    }
+
+    /// Install `WH_MOUSE_LL`/`WH_KEYBOARD_LL` hooks that swallow local
+    /// mouse/keyboard input and hand each event to `callback` instead of
+    /// letting it reach the focused app - the "am I the controller" half of
+    /// the edge-follow handoff in `network::check_edge_transition`. Hooks
+    /// only actually fire while the installing thread is pumping messages,
+    /// so this runs on its own thread.
+    pub fn start_capture<F>(callback: F)
+    where
+        F: Fn(super::InputEvent) + Send + 'static,
+    {
+        *CAPTURE_CALLBACK.lock().unwrap() = Some(Box::new(callback));
+
+        std::thread::spawn(|| unsafe {
+            *CAPTURE_THREAD_ID.lock().unwrap() = Some(GetCurrentThreadId());
+
+            let mouse_hook = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), None, 0).ok();
+            let keyboard_hook = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), None, 0).ok();
+            *MOUSE_HOOK.lock().unwrap() = mouse_hook.map(|h| h.0);
+            *KEYBOARD_HOOK.lock().unwrap() = keyboard_hook.map(|h| h.0);
+
+            println!("🎯 Input capture hooks installed");
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+
+            println!("🎯 Input capture message pump exited");
+        });
+    }
+
+    /// Stop the hooks installed by `start_capture`, letting local input
+    /// reach apps again.
+    pub fn stop_capture() {
+        unsafe {
+            if let Some(h) = MOUSE_HOOK.lock().unwrap().take() {
+                let _ = UnhookWindowsHookEx(HHOOK(h));
+            }
+            if let Some(h) = KEYBOARD_HOOK.lock().unwrap().take() {
+                let _ = UnhookWindowsHookEx(HHOOK(h));
+            }
+            if let Some(thread_id) = CAPTURE_THREAD_ID.lock().unwrap().take() {
+                let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+        }
+        *CAPTURE_CALLBACK.lock().unwrap() = None;
+        println!("🎯 Input capture hooks removed");
+    }
 }
 
 // ============= Fallback =============
@@ -399,12 +687,182 @@ mod platform {
         }]
     }
     pub fn get_mouse_position() -> (i32, i32) { (0, 0) }
-    pub fn move_mouse(_x: i32, _y: i32) {}
-    pub fn mouse_click(_button: &str, _action: &str) {}
-    pub fn key_event(_key_code: u32, _action: &str) {}
-    pub fn scroll(_delta_x: i32, _delta_y: i32) {}
     pub fn hide_cursor() {}
     pub fn show_cursor() {}
+    pub fn set_cursor(_shape: super::CursorShape) {}
+    /// No OS-level change counter on this target; 0 tells callers "unsupported"
+    /// so they fall back to always re-hashing instead of trusting a stuck value.
+    pub fn clipboard_sequence() -> u64 { 0 }
+    pub fn start_capture<F>(_callback: F) where F: Fn(super::InputEvent) + Send + 'static {}
+    pub fn stop_capture() {}
 }
 
 pub use platform::*;
+
+// ============= Injection backend =============
+//
+// `get_screen_size`/`get_all_screens`/`get_mouse_position`/`hide_cursor`/
+// `show_cursor` above stay hand-rolled per OS since they're not things
+// `enigo` covers. Actually *injecting* input used to be hand-rolled per OS
+// too; it's now behind `InputBackend` so the network layer (and anything
+// else forwarding remote input) doesn't need to know or care which crate
+// does the injecting. `EnigoBackend` is the only implementation today, but
+// a future Linux (X11/uinput) backend can implement the trait without
+// touching a single call site outside this file.
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+pub trait InputBackend: Send {
+    fn move_mouse(&mut self, x: i32, y: i32);
+    fn click(&mut self, button: &str, action: &str);
+    fn scroll(&mut self, delta_x: i32, delta_y: i32, precise: bool);
+    fn key(&mut self, key_code: u32, action: &str);
+    fn type_text(&mut self, text: &str);
+}
+
+/// Fixed-point scroll resolution used on the wire, matching the granularity
+/// Windows' `WHEEL_DELTA` already uses: one full notch of a discrete mouse
+/// wheel is `TICKS_PER_NOTCH` units. `enigo`'s scroll API only takes whole
+/// "clicks" though, so a precise (trackpad momentum) event divides by a much
+/// finer step to avoid rounding an entire gesture down to zero.
+const TICKS_PER_NOTCH: i32 = 120;
+const PRECISE_TICKS_PER_STEP: i32 = TICKS_PER_NOTCH / 12;
+
+pub struct EnigoBackend {
+    enigo: enigo::Enigo,
+    /// Sub-step remainder left over from the last `scroll` call, per axis.
+    /// `enigo` only moves in whole notches, so a fast run of small trackpad
+    /// deltas (each below `step`) would otherwise round to zero on every
+    /// single call and the gesture would never actually scroll anything -
+    /// accumulating here lets those remainders carry over until they add up
+    /// to a whole notch.
+    scroll_remainder: (i32, i32),
+}
+
+impl EnigoBackend {
+    fn new() -> Self {
+        EnigoBackend {
+            enigo: enigo::Enigo::new(&enigo::Settings::default())
+                .expect("failed to initialize enigo input backend"),
+            scroll_remainder: (0, 0),
+        }
+    }
+}
+
+impl InputBackend for EnigoBackend {
+    fn move_mouse(&mut self, x: i32, y: i32) {
+        use enigo::Mouse;
+        if let Err(e) = self.enigo.move_mouse(x, y, enigo::Coordinate::Abs) {
+            println!("⚠️ enigo move_mouse({}, {}) failed: {:?}", x, y, e);
+        }
+    }
+
+    fn click(&mut self, button: &str, action: &str) {
+        use enigo::Mouse;
+        let Some(mapped) = (match button {
+            "left" => Some(enigo::Button::Left),
+            "right" => Some(enigo::Button::Right),
+            "middle" => Some(enigo::Button::Middle),
+            _ => None,
+        }) else {
+            return;
+        };
+        let Some(direction) = direction_for(action) else { return };
+        if let Err(e) = self.enigo.button(mapped, direction) {
+            println!("⚠️ enigo click({}, {}) failed: {:?}", button, action, e);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn scroll(&mut self, delta_x: i32, delta_y: i32, precise: bool) {
+        // `enigo`'s scroll API only moves in whole wheel "clicks", which
+        // can't express pixel-precise trackpad momentum. macOS has a native
+        // event for exactly that, so bypass enigo here entirely instead of
+        // rounding every precise delta down to the nearest notch. Precise
+        // ticks are posted as pixels unchanged; wheel ticks are converted to
+        // whole lines (one per `TICKS_PER_NOTCH`), carrying the remainder.
+        if precise {
+            platform::post_scroll_event(delta_x, delta_y, true);
+            return;
+        }
+        let (rem_x, rem_y) = self.scroll_remainder;
+        let (total_x, total_y) = (rem_x + delta_x, rem_y + delta_y);
+        let (lines_x, lines_y) = (total_x / TICKS_PER_NOTCH, total_y / TICKS_PER_NOTCH);
+        self.scroll_remainder = (total_x - lines_x * TICKS_PER_NOTCH, total_y - lines_y * TICKS_PER_NOTCH);
+        if lines_x != 0 || lines_y != 0 {
+            platform::post_scroll_event(lines_x, lines_y, false);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn scroll(&mut self, delta_x: i32, delta_y: i32, precise: bool) {
+        use enigo::Mouse;
+        let step = if precise { PRECISE_TICKS_PER_STEP } else { TICKS_PER_NOTCH };
+        let (rem_x, rem_y) = self.scroll_remainder;
+        let total_x = rem_x + delta_x;
+        let total_y = rem_y + delta_y;
+        let notches_x = total_x / step;
+        let notches_y = total_y / step;
+        self.scroll_remainder = (total_x - notches_x * step, total_y - notches_y * step);
+        if notches_y != 0 {
+            let _ = self.enigo.scroll(notches_y, enigo::Axis::Vertical);
+        }
+        if notches_x != 0 {
+            let _ = self.enigo.scroll(notches_x, enigo::Axis::Horizontal);
+        }
+    }
+
+    fn key(&mut self, key_code: u32, action: &str) {
+        use enigo::Keyboard;
+        let Some(direction) = direction_for(action) else { return };
+        let _ = self.enigo.key(enigo::Key::Other(key_code), direction);
+    }
+
+    // macOS and Windows bypass enigo here and inject the Unicode text
+    // directly via `platform::type_text` - see its doc comment in each
+    // platform block for why key-code translation can't express this.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn type_text(&mut self, text: &str) {
+        platform::type_text(text);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn type_text(&mut self, text: &str) {
+        use enigo::Keyboard;
+        if let Err(e) = self.enigo.text(text) {
+            println!("⚠️ enigo type_text failed: {:?}", e);
+        }
+    }
+}
+
+// "down"/"up" are the spellings `start_capture` reports for a locally
+// captured event, forwarded as-is by `network::start_input_capture`.
+fn direction_for(action: &str) -> Option<enigo::Direction> {
+    match action {
+        "press" | "down" => Some(enigo::Direction::Press),
+        "release" | "up" => Some(enigo::Direction::Release),
+        _ => None,
+    }
+}
+
+static BACKEND: Lazy<Mutex<EnigoBackend>> = Lazy::new(|| Mutex::new(EnigoBackend::new()));
+
+pub fn move_mouse(x: i32, y: i32) {
+    BACKEND.lock().unwrap().move_mouse(x, y);
+}
+
+pub fn mouse_click(button: &str, action: &str) {
+    BACKEND.lock().unwrap().click(button, action);
+}
+
+pub fn key_event(key_code: u32, action: &str) {
+    BACKEND.lock().unwrap().key(key_code, action);
+}
+
+pub fn scroll(delta_x: i32, delta_y: i32, precise: bool) {
+    BACKEND.lock().unwrap().scroll(delta_x, delta_y, precise);
+}
+
+pub fn type_text(text: &str) {
+    BACKEND.lock().unwrap().type_text(text);
+}
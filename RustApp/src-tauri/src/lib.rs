@@ -4,7 +4,14 @@
 mod network;
 mod input;
 mod clipboard_sync;
-
+mod codec;
+mod crypto;
+mod config;
+mod screen_stream;
+mod auth;
+mod transport;
+
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use tauri::State;
@@ -51,6 +58,10 @@ pub struct InputEvent {
     pub button: Option<String>,
     pub key_code: Option<u32>,
     pub modifiers: Option<Modifiers>,
+    pub scroll_x: Option<i32>,
+    pub scroll_y: Option<i32>,
+    /// Unicode/IME text to type directly, for dead-key sequences a single `key_code` can't express.
+    pub text: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
@@ -132,10 +143,40 @@ pub struct PeerInfo {
     pub computer_type: String,
 }
 
+/// Snapshot the bits of app state we persist across restarts.
+fn build_config(app_state: &AppState) -> config::PersistedConfig {
+    config::PersistedConfig {
+        format_version: config::CURRENT_FORMAT_VERSION,
+        computers: app_state.computers.clone(),
+        clipboard_sync_enabled: app_state.clipboard_sync_enabled,
+        screen_layout: network::REMOTE_EDGE.read().unwrap().clone(),
+        transport: transport_mode_string(transport::get_transport_kind()),
+    }
+}
+
+fn transport_mode_string(kind: transport::TransportKind) -> String {
+    match kind {
+        transport::TransportKind::Tcp => "tcp".to_string(),
+        transport::TransportKind::Quic => "quic".to_string(),
+    }
+}
+
+fn transport_kind_from_str(mode: &str) -> Result<transport::TransportKind, String> {
+    match mode {
+        "tcp" => Ok(transport::TransportKind::Tcp),
+        "quic" => Ok(transport::TransportKind::Quic),
+        other => Err(format!("Invalid transport: {}. Must be \"tcp\" or \"quic\"", other)),
+    }
+}
+
+fn persist_config(app_state: &AppState) {
+    config::save(&build_config(app_state));
+}
+
 #[tauri::command]
 fn add_computer(name: String, ip: String, position: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let mut app_state = state.lock().map_err(|e| e.to_string())?;
-    
+
     app_state.computers.push(ComputerInfo {
         name,
         ip,
@@ -144,6 +185,7 @@ fn add_computer(name: String, ip: String, position: String, state: State<'_, Arc
         screen_width: 1920,
         screen_height: 1080,
     });
+    persist_config(&app_state);
     Ok(())
 }
 
@@ -157,6 +199,7 @@ fn get_computers(state: State<'_, Arc<Mutex<AppState>>>) -> Result<Vec<ComputerI
 fn remove_computer(ip: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let mut app_state = state.lock().map_err(|e| e.to_string())?;
     app_state.computers.retain(|c| c.ip != ip);
+    persist_config(&app_state);
     Ok(())
 }
 
@@ -164,9 +207,16 @@ fn remove_computer(ip: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result
 fn set_clipboard_sync(enabled: bool, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let mut app_state = state.lock().map_err(|e| e.to_string())?;
     app_state.clipboard_sync_enabled = enabled;
+    network::set_clipboard_sync_enabled(enabled);
+    persist_config(&app_state);
     Ok(())
 }
 
+#[tauri::command]
+fn set_clipboard_poll_interval(ms: u64) {
+    clipboard_sync::set_poll_interval_ms(ms);
+}
+
 #[tauri::command]
 fn get_clipboard_text() -> Result<String, String> {
     clipboard_sync::get_text().map_err(|e| e.to_string())
@@ -177,6 +227,30 @@ fn set_clipboard_text(text: String) -> Result<(), String> {
     clipboard_sync::set_text(&text).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_clipboard_image() -> Result<String, String> {
+    let png = clipboard_sync::get_image().map_err(|e| e.to_string())?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+#[tauri::command]
+fn set_clipboard_image(base64_png: String) -> Result<(), String> {
+    let png = base64::engine::general_purpose::STANDARD
+        .decode(base64_png)
+        .map_err(|e| e.to_string())?;
+    clipboard_sync::set_image(&png).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_remote_clipboard_formats() -> Vec<String> {
+    network::get_remote_clipboard_formats()
+}
+
+#[tauri::command]
+async fn request_remote_clipboard(format: String) {
+    network::request_remote_clipboard(&format).await;
+}
+
 #[tauri::command]
 fn get_screen_info() -> (i32, i32) {
     input::get_screen_size()
@@ -226,7 +300,7 @@ fn set_remote_screens(screens: Vec<RemoteScreenInfo>, state: State<'_, Arc<Mutex
 }
 
 #[tauri::command]
-fn set_screen_layout(remote_edge: String) -> Result<(), String> {
+fn set_screen_layout(remote_edge: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     // Set which edge leads to remote screens
     // Valid values: "right", "left", "top", "bottom"
     let valid_edges = ["right", "left", "top", "bottom"];
@@ -235,6 +309,9 @@ fn set_screen_layout(remote_edge: String) -> Result<(), String> {
     }
     *network::REMOTE_EDGE.write().unwrap() = remote_edge.clone();
     println!("📐 Screen layout updated: Windows is to the {} of Mac", remote_edge);
+
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    persist_config(&app_state);
     Ok(())
 }
 
@@ -262,6 +339,76 @@ pub struct DebugInfoResponse {
     pub edge_status: String,
     pub remote_screen_count: usize,
     pub last_update: u64,
+    pub auth_status: String,
+}
+
+#[tauri::command]
+fn get_connection_security() -> network::ConnectionSecurity {
+    network::get_connection_security()
+}
+
+#[tauri::command]
+fn set_pairing_code(code: Option<String>) {
+    network::set_pairing_code(code);
+}
+
+/// Switch between the TCP and QUIC transports. Takes effect for connections
+/// dialed from now on - it doesn't tear down one already in progress, and the
+/// listener `network::start_auto_discovery` started at launch keeps accepting
+/// only the transport that was configured then.
+#[tauri::command]
+fn set_transport_mode(mode: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let kind = transport_kind_from_str(&mode)?;
+    transport::set_transport_kind(kind);
+
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    persist_config(&app_state);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_transport_mode() -> String {
+    transport_mode_string(transport::get_transport_kind())
+}
+
+#[tauri::command]
+fn export_config(state: State<'_, Arc<Mutex<AppState>>>) -> Result<String, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    config::export_to_string(&build_config(&app_state))
+}
+
+#[tauri::command]
+fn import_config(yaml: String, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let imported = config::import_from_string(&yaml)?;
+
+    let mut app_state = state.lock().map_err(|e| e.to_string())?;
+    app_state.computers = imported.computers.clone();
+    app_state.clipboard_sync_enabled = imported.clipboard_sync_enabled;
+    *network::REMOTE_EDGE.write().unwrap() = imported.screen_layout.clone();
+    network::set_clipboard_sync_enabled(imported.clipboard_sync_enabled);
+    if let Ok(kind) = transport_kind_from_str(&imported.transport) {
+        transport::set_transport_kind(kind);
+    }
+
+    config::save(&build_config(&app_state));
+    Ok(())
+}
+
+/// Start streaming our active display to the connected peer. `fps` is
+/// clamped to a sane range by `screen_stream::start_streaming`.
+#[tauri::command]
+async fn start_screen_streaming(fps: u32) -> Result<(), String> {
+    screen_stream::start_streaming(fps).await
+}
+
+#[tauri::command]
+fn stop_screen_streaming() {
+    screen_stream::stop_streaming();
+}
+
+#[tauri::command]
+fn is_screen_streaming() -> bool {
+    screen_stream::is_streaming()
 }
 
 #[tauri::command]
@@ -274,18 +421,26 @@ fn get_debug_info() -> DebugInfoResponse {
         edge_status: debug.edge_status,
         remote_screen_count: debug.remote_screen_count,
         last_update: debug.last_update,
+        auth_status: debug.auth_status,
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let persisted = config::load();
+    *network::REMOTE_EDGE.write().unwrap() = persisted.screen_layout.clone();
+    network::set_clipboard_sync_enabled(persisted.clipboard_sync_enabled);
+    if let Ok(kind) = transport_kind_from_str(&persisted.transport) {
+        transport::set_transport_kind(kind);
+    }
+
     let app_state = Arc::new(Mutex::new(AppState {
         is_server: false,
         is_connected: false,
         active_computer: None,
-        computers: Vec::new(),
+        computers: persisted.computers,
         local_ip: get_local_ip(),
-        clipboard_sync_enabled: true,
+        clipboard_sync_enabled: persisted.clipboard_sync_enabled,
         remote_screens: Vec::new(),
     }));
 
@@ -306,6 +461,10 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(app_state)
+        .setup(|app| {
+            screen_stream::set_app_handle(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_local_ip,
             get_computer_name,
@@ -317,6 +476,11 @@ pub fn run() {
             set_clipboard_sync,
             get_clipboard_text,
             set_clipboard_text,
+            get_clipboard_image,
+            set_clipboard_image,
+            get_remote_clipboard_formats,
+            request_remote_clipboard,
+            set_clipboard_poll_interval,
             get_screen_info,
             get_all_screens,
             get_mouse_position,
@@ -329,6 +493,15 @@ pub fn run() {
             get_synced_layout,
             send_layout_sync,
             get_connection_status,
+            get_connection_security,
+            set_pairing_code,
+            set_transport_mode,
+            get_transport_mode,
+            export_config,
+            import_config,
+            start_screen_streaming,
+            stop_screen_streaming,
+            is_screen_streaming,
             get_debug_info,
         ])
         .run(tauri::generate_context!())
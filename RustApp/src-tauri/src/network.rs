@@ -1,19 +1,55 @@
 // Network module - TCP server and client for input sharing
 // Auto-discovery via UDP broadcast - no manual IP needed!
 
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream, UdpSocket};
-use tokio::net::tcp::OwnedWriteHalf;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, AsyncBufReadExt, BufReader};
+use tokio::sync::Mutex;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::sync::RwLock;
-use std::net::SocketAddr;
+
+use crate::auth;
+use crate::codec::MessageCodec;
+use crate::crypto;
+use crate::transport::{self, BoxedReader, BoxedWriter, Duplex};
 
 const TCP_PORT: u16 = 52525;
 const UDP_PORT: u16 = 52526;
 const DISCOVERY_MAGIC: &str = "MACWINCTRL";
+const PROTOCOL_VERSION: &str = "2.0";
+
+/// First 4 bytes of every connection, derived from `DISCOVERY_MAGIC` so a
+/// socket that isn't actually a MacWinControl peer (or a much older build
+/// that predates this negotiation step) is rejected before either side
+/// commits to the crypto handshake.
+const PROTOCOL_MAGIC: [u8; 4] = [b'M', b'C', b'W', b'C'];
+
+/// Bump whenever `Message`'s wire shape changes in a way an older peer can't
+/// safely ignore. `negotiate_version` records `min(local, remote)`, and
+/// `Message::min_version` gates newer variants behind it so a mixed-version
+/// Mac/Windows pair degrades gracefully instead of misinterpreting a
+/// message type it predates.
+const PROTOCOL_VERSION_NUM: u16 = 2;
+
+/// Oldest protocol version `negotiate_version` will still agree to speak.
+/// A peer advertising anything below this predates a wire-format change we
+/// can no longer interpret safely (e.g. the switch to the length-delimited
+/// binary codec), so the connection is aborted rather than negotiated down.
+const MIN_PROTO: u16 = 1;
+
+/// Newest protocol version we understand - currently just `PROTOCOL_VERSION_NUM`,
+/// kept as a separate constant so the "supported range" reads the same way
+/// at both ends instead of mixing a bare version number with a range.
+const MAX_PROTO: u16 = PROTOCOL_VERSION_NUM;
+
+// The default `codec::MessageCodec` frame cap is sized for input/clipboard
+// traffic; screen-stream keyframes can run larger, so connections that may
+// carry `Message::ScreenFrame` raise it.
+const SCREEN_STREAM_MAX_FRAME: usize = 8 * 1024 * 1024;
 
 // Global storage for received remote screens
 pub static REMOTE_SCREENS: Lazy<RwLock<Vec<ReceivedScreen>>> = Lazy::new(|| RwLock::new(Vec::new()));
@@ -21,17 +57,34 @@ pub static REMOTE_SCREENS: Lazy<RwLock<Vec<ReceivedScreen>>> = Lazy::new(|| RwLo
 // Global storage for discovered peers
 pub static DISCOVERED_PEERS: Lazy<RwLock<Vec<DiscoveredPeer>>> = Lazy::new(|| RwLock::new(Vec::new()));
 
+/// Random per-process tie-breaker for simultaneous discovery: if both peers'
+/// `start_udp_listener` see each other in the same tick they'd otherwise
+/// both dial, leaving each side with an inbound socket it can't write to
+/// and an outbound one it can (see `should_initiate_connection`). Generated
+/// once at startup and broadcast alongside `proto_ver`.
+static LOCAL_NONCE: Lazy<u64> = Lazy::new(|| {
+    use rand::RngCore;
+    rand::rngs::OsRng.next_u64()
+});
+
 // Connection state
 pub static IS_CONNECTED: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
 pub static CONNECTED_TO: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
 
-// Separate write half for sending messages (avoids deadlock with read loop)
-pub static WRITE_STREAM: Lazy<RwLock<Option<Arc<Mutex<OwnedWriteHalf>>>>> = Lazy::new(|| RwLock::new(None));
+// Write half of the outgoing connection, framed with the binary wire codec.
+// Kept separate from the read loop so sends never block on an in-flight read.
+// Boxed so the same static covers both the TCP and QUIC transports - see
+// `transport::BoxedWriter`.
+pub static WRITE_STREAM: Lazy<RwLock<Option<Arc<Mutex<FramedWrite<BoxedWriter, MessageCodec>>>>>> =
+    Lazy::new(|| RwLock::new(None));
 
-// Legacy - still used for some things but being phased out
-pub static ACTIVE_CLIENT: Lazy<RwLock<Option<Arc<Mutex<TcpStream>>>>> = Lazy::new(|| RwLock::new(None));
-// Track if ACTIVE_CLIENT is an outgoing connection (we initiated it)
-pub static IS_OUTGOING_CONNECTION: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+// Only set when the active connection is QUIC: the second bidirectional
+// stream reserved for bulk screen/layout/clipboard traffic, so it can never
+// head-of-line-block whatever's waiting on `WRITE_STREAM`. `None` under TCP,
+// where there's only the one stream - `send_to_peer` falls back to
+// `WRITE_STREAM` for bulk messages in that case.
+pub static BULK_WRITE_STREAM: Lazy<RwLock<Option<Arc<Mutex<FramedWrite<BoxedWriter, MessageCodec>>>>>> =
+    Lazy::new(|| RwLock::new(None));
 
 // Control state - which computer has mouse/keyboard control
 pub static CONTROL_ACTIVE: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));  // true = we're controlling remote
@@ -54,6 +107,54 @@ pub static SYNCED_LAYOUT: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::ne
 // Debug state for UI
 pub static DEBUG_INFO: Lazy<RwLock<DebugInfo>> = Lazy::new(|| RwLock::new(DebugInfo::default()));
 
+// Pairing code the user enters on both machines; mixed into the handshake's
+// key derivation so a mismatched code yields a session neither side can
+// decrypt. `None` means "no pairing code configured" (ECDH alone still
+// protects against passive eavesdroppers, just not a LAN MITM).
+pub static PAIRING_CODE: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+// Security state of the active connection, surfaced to the frontend via
+// `get_connection_security()`.
+pub static CONNECTION_SECURITY: Lazy<RwLock<ConnectionSecurity>> =
+    Lazy::new(|| RwLock::new(ConnectionSecurity::default()));
+
+#[derive(Clone, Serialize, Debug, Default)]
+pub struct ConnectionSecurity {
+    pub encrypted: bool,
+    pub peer_fingerprint: Option<String>,
+    /// Whether the pre-shared-key challenge/response (see `auth`) has
+    /// completed for this connection. `encrypted` flips to `true` as soon as
+    /// the ECDH handshake finishes, which happens *before* auth - a
+    /// connection can be encrypted without being authenticated yet, and
+    /// `WRITE_STREAM`/`IS_CONNECTED` are never populated until this is also
+    /// `true` (see `dispatch_message`'s `authenticated` gate).
+    pub authenticated: bool,
+}
+
+// Whether clipboard changes should be picked up and sent to the peer.
+// Mirrors `AppState::clipboard_sync_enabled`, kept here too since the
+// watcher task lives in this module and has no access to Tauri state.
+pub static CLIPBOARD_SYNC_ENABLED: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(true));
+
+// Input events (moves, scrolls, clicks, keys) waiting to go out in the next
+// `Message::InputBatch` - see `queue_batched_input` and `start_input_batching`.
+static INPUT_BATCH: Lazy<Mutex<Vec<BatchedInput>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// How long to let events pile up before flushing them as one `InputBatch`.
+/// Short enough that click-drag and modifier-key ordering still feel
+/// instantaneous, long enough to turn a fast drag's many per-sample writes
+/// into one.
+const INPUT_BATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(6);
+
+/// How often `start_peer_gossip` relays our known-peer table to the
+/// connected peer.
+const PEER_GOSSIP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A `DiscoveredPeer` not refreshed (by UDP broadcast or gossip) within this
+/// long is dropped by `prune_stale_peers` - UDP-only discovery never expired
+/// entries, so a peer taken off the network stayed in the list forever.
+const PEER_STALE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
 #[derive(Clone, Serialize, Debug, Default)]
 pub struct DebugInfo {
     pub mouse_x: i32,
@@ -62,6 +163,10 @@ pub struct DebugInfo {
     pub edge_status: String,
     pub remote_screen_count: usize,
     pub last_update: u64,
+    /// Human-readable summary of `CONNECTION_SECURITY`, so the debug view
+    /// makes it obvious when a connection is encrypted-but-not-yet-
+    /// authenticated (no input forwarded yet) versus fully up.
+    pub auth_status: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -70,6 +175,36 @@ pub struct DiscoveredPeer {
     pub ip: String,
     pub computer_type: String,
     pub last_seen: u64,
+    /// Handshake fingerprint (see `crypto::fingerprint_hex`) this `name` was
+    /// first seen with, filled in once a `hello` arrives - `None` until then
+    /// (UDP discovery alone never proves identity). Used by
+    /// `check_peer_identity` to pin the name to that key: a later `hello`
+    /// claiming the same name with a different key is a spoof, not a
+    /// reconnect, and is rejected rather than overwriting it.
+    pub key: Option<String>,
+    /// Protocol version this peer advertised in its UDP broadcast. Purely
+    /// informational at discovery time - the real negotiation (and the
+    /// `[MIN_PROTO, MAX_PROTO]` bounds check) still happens over TCP in
+    /// `negotiate_version` - but it lets `start_udp_listener` skip
+    /// auto-connecting to an obviously incompatible peer.
+    pub proto_ver: u16,
+    /// Peer's `LOCAL_NONCE`, used with its IP in `should_initiate_connection`
+    /// to break simultaneous-open races.
+    pub nonce: u64,
+}
+
+/// One entry in a `Message::PeerGossip` table - a trimmed-down
+/// `DiscoveredPeer` carrying only what's safe (and useful) to relay
+/// transitively. Deliberately excludes `key` (a pinned fingerprint only
+/// means something to the peer that actually completed that handshake) and
+/// `nonce` (the simultaneous-open tie-break only matters for direct UDP
+/// discovery, not a peer we merely heard about secondhand).
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GossipPeer {
+    pub name: String,
+    pub ip: String,
+    pub computer_type: String,
+    pub last_seen: u64,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -94,498 +229,1023 @@ pub struct ScreenData {
     pub is_primary: bool,
 }
 
+/// The one message type that travels over the wire in both directions.
+/// Encoded/decoded by `codec::MessageCodec` - see that module for the framing.
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Message {
-    #[serde(rename = "type")]
-    pub msg_type: String,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub x: Option<i32>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub y: Option<i32>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub button: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub action: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub key_code: Option<u32>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub screens: Option<Vec<ScreenData>>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub computer_type: Option<String>,
-    
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub layout: Option<String>,  // JSON string of saved layout positions
+pub enum Message {
+    /// Sent by the server immediately after the crypto handshake, before
+    /// anything else: a random nonce the client must HMAC with the shared
+    /// secret to prove it's authorized. See `auth`.
+    AuthChallenge(Vec<u8>),
+    AuthResponse {
+        name: String,
+        hmac_hex: String,
+    },
+    AuthStatus {
+        ok: bool,
+    },
+    Hello {
+        name: String,
+        version: String,
+        screens: Vec<ScreenData>,
+        computer_type: String,
+    },
+    MouseMove { x: i32, y: i32 },
+    MouseButton { button: String, action: String },
+    Key { key_code: u32, action: String },
+    /// `delta_x`/`delta_y` are fixed-point ticks (120 per notch, matching OS
+    /// wheel granularity - see `input::TICKS_PER_NOTCH`); `precise` marks a
+    /// trackpad/momentum event so the receiver scrolls in finer steps
+    /// instead of rounding a whole gesture down to one notch.
+    Scroll { delta_x: i32, delta_y: i32, precise: bool },
+    /// Unicode/IME text to type directly, bypassing key codes entirely.
+    TypeText(String),
+    /// What the controlling side's cursor should look like right now, so
+    /// the controlled machine can mirror whatever's under the remote
+    /// pointer (I-beam over text, resize arrows over a border, ...) via
+    /// `input::set_cursor` instead of showing a plain arrow the whole time
+    /// control is handed off.
+    CursorShape(crate::input::CursorShape),
+    /// A short-window coalesced batch of input events (moves, scrolls,
+    /// clicks, keys), replayed in order on arrival. See `queue_batched_input`
+    /// - one socket write per burst instead of one per sample.
+    InputBatch(Vec<BatchedInput>),
+    ClipboardText(String),
+    /// PNG-encoded image bytes, see `clipboard_sync::{get_image, set_image}`.
+    ClipboardImage(Vec<u8>),
+    /// Announces which MIME formats are available on the sender's clipboard
+    /// right now, without the payload itself - sent instead of eagerly
+    /// pushing `ClipboardImage` on every change so a large image isn't
+    /// retransmitted on every selection tweak. See `ClipboardRequest` and
+    /// `handle_clipboard_offer`.
+    ClipboardOffer { formats: Vec<String> },
+    /// Asks the peer to actually send the payload for one of the formats it
+    /// last offered. See `handle_clipboard_request`.
+    ClipboardRequest { format: String },
+    LayoutSync(String),
+    ControlStart { x: i32, y: i32 },
+    ControlEnd,
+    /// This node's known-peer table, gossiped periodically to every
+    /// connected peer so two machines that can't hear each other's UDP
+    /// broadcasts directly (different subnets) still become discoverable
+    /// through any transitive TCP link. See `start_peer_gossip` and
+    /// `merge_gossiped_peers`.
+    PeerGossip(Vec<GossipPeer>),
+    /// A JPEG-encoded patch of the sender's active display, see `screen_stream`.
+    ScreenFrame {
+        frame_width: u32,
+        frame_height: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        jpeg: Vec<u8>,
+    },
+    Heartbeat,
+}
+
+/// One event inside an `Message::InputBatch`. Mirrors the standalone
+/// per-event `Message` variants rather than wrapping a whole `Message`,
+/// since a batch can only ever carry these five input kinds.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum BatchedInput {
+    MouseMove { x: i32, y: i32 },
+    MouseButton { button: String, action: String },
+    Key { key_code: u32, action: String },
+    Scroll { delta_x: i32, delta_y: i32, precise: bool },
+    TypeText(String),
 }
 
 impl Message {
-    pub fn hello_with_screens(name: &str, screens: Vec<ScreenData>, computer_type: &str) -> Self {
-        Message {
-            msg_type: "hello".to_string(),
-            name: Some(name.to_string()),
-            version: Some("1.0".to_string()),
-            screens: Some(screens),
-            computer_type: Some(computer_type.to_string()),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, text: None, layout: None,
-        }
+    pub fn auth_challenge(nonce: Vec<u8>) -> Self {
+        Message::AuthChallenge(nonce)
+    }
+
+    pub fn auth_response(name: &str, hmac_hex: String) -> Self {
+        Message::AuthResponse { name: name.to_string(), hmac_hex }
+    }
+
+    pub fn auth_status(ok: bool) -> Self {
+        Message::AuthStatus { ok }
     }
-    
-    pub fn hello(name: &str) -> Self {
-        Message {
-            msg_type: "hello".to_string(),
-            name: Some(name.to_string()),
-            version: Some("1.0".to_string()),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, text: None, screens: None, computer_type: None, layout: None,
+
+    pub fn hello_with_screens(name: &str, screens: Vec<ScreenData>, computer_type: &str) -> Self {
+        Message::Hello {
+            name: name.to_string(),
+            version: PROTOCOL_VERSION.to_string(),
+            screens,
+            computer_type: computer_type.to_string(),
         }
     }
-    
+
     pub fn mouse_move(x: i32, y: i32) -> Self {
-        Message {
-            msg_type: "mouse_move".to_string(),
-            x: Some(x),
-            y: Some(y),
-            button: None, action: None, key_code: None, 
-            text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+        Message::MouseMove { x, y }
     }
-    
+
     pub fn mouse_click(button: &str, action: &str) -> Self {
-        Message {
-            msg_type: "mouse_click".to_string(),
-            button: Some(button.to_string()),
-            action: Some(action.to_string()),
-            x: None, y: None, key_code: None, 
-            text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+        Message::MouseButton { button: button.to_string(), action: action.to_string() }
     }
-    
+
     pub fn key_event(key_code: u32, action: &str) -> Self {
-        Message {
-            msg_type: "key_event".to_string(),
-            key_code: Some(key_code),
-            action: Some(action.to_string()),
-            x: None, y: None, button: None, 
-            text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+        Message::Key { key_code, action: action.to_string() }
+    }
+
+    pub fn scroll(delta_x: i32, delta_y: i32, precise: bool) -> Self {
+        Message::Scroll { delta_x, delta_y, precise }
+    }
+
+    pub fn type_text(text: &str) -> Self {
+        Message::TypeText(text.to_string())
+    }
+
+    pub fn cursor_shape(shape: crate::input::CursorShape) -> Self {
+        Message::CursorShape(shape)
+    }
+
+    pub fn input_batch(events: Vec<BatchedInput>) -> Self {
+        Message::InputBatch(events)
     }
-    
+
     pub fn clipboard(text: &str) -> Self {
-        Message {
-            msg_type: "clipboard".to_string(),
-            text: Some(text.to_string()),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+        Message::ClipboardText(text.to_string())
     }
-    
-    pub fn ping() -> Self {
-        Message {
-            msg_type: "ping".to_string(),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+
+    pub fn clipboard_image(png_bytes: Vec<u8>) -> Self {
+        Message::ClipboardImage(png_bytes)
     }
-    
-    pub fn pong() -> Self {
-        Message {
-            msg_type: "pong".to_string(),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        }
+
+    pub fn clipboard_offer(formats: Vec<String>) -> Self {
+        Message::ClipboardOffer { formats }
+    }
+
+    pub fn clipboard_request(format: &str) -> Self {
+        Message::ClipboardRequest { format: format.to_string() }
     }
-    
+
     pub fn layout_sync(layout_json: &str) -> Self {
-        Message {
-            msg_type: "layout_sync".to_string(),
-            layout: Some(layout_json.to_string()),
-            x: None, y: None, button: None, action: None, 
-            key_code: None, text: None, name: None, version: None,
-            screens: None, computer_type: None,
+        Message::LayoutSync(layout_json.to_string())
+    }
+
+    pub fn control_start(x: i32, y: i32) -> Self {
+        Message::ControlStart { x, y }
+    }
+
+    pub fn control_end() -> Self {
+        Message::ControlEnd
+    }
+
+    pub fn peer_gossip(peers: Vec<GossipPeer>) -> Self {
+        Message::PeerGossip(peers)
+    }
+
+    pub fn screen_frame(
+        frame_width: u32,
+        frame_height: u32,
+        region: crate::screen_stream::DirtyRegion,
+        jpeg: Vec<u8>,
+    ) -> Self {
+        let (x, y, width, height) = region;
+        Message::ScreenFrame { frame_width, frame_height, x, y, width, height, jpeg }
+    }
+
+    /// Lowest negotiated protocol version that understands this variant.
+    /// Dispatch skips (rather than acts on) a message above the peer's
+    /// negotiated version - see `negotiate_version` and `dispatch_message`.
+    fn min_version(&self) -> u16 {
+        match self {
+            Message::Scroll { .. }
+            | Message::TypeText(_)
+            | Message::CursorShape(_)
+            | Message::ScreenFrame { .. }
+            | Message::ClipboardImage(_)
+            | Message::InputBatch(_)
+            | Message::PeerGossip(_)
+            | Message::ClipboardOffer { .. }
+            | Message::ClipboardRequest { .. } => 2,
+            _ => 1,
         }
     }
+
+    /// Whether this variant belongs on the bulk stream rather than the
+    /// latency-critical input one, under a transport that actually
+    /// distinguishes the two (QUIC - see `BULK_WRITE_STREAM`). Over TCP,
+    /// where there's only one stream, this has no effect.
+    fn is_bulk(&self) -> bool {
+        matches!(
+            self,
+            Message::Hello { .. }
+                | Message::LayoutSync(_)
+                | Message::ClipboardText(_)
+                | Message::ClipboardImage(_)
+                | Message::ScreenFrame { .. }
+                | Message::PeerGossip(_)
+        )
+    }
 }
 
-pub type ClientList = Arc<Mutex<Vec<Arc<Mutex<TcpStream>>>>>;
+/// Exchange `PROTOCOL_MAGIC` + `PROTOCOL_VERSION_NUM` as the very first bytes
+/// on a connection, before the crypto handshake. Returns the negotiated
+/// version (`min(local, remote)`) or an error if the peer's magic doesn't
+/// match (i.e. it isn't a MacWinControl peer at all).
+async fn negotiate_version<S>(stream: &mut S) -> std::io::Result<u16>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(&PROTOCOL_MAGIC).await?;
+    stream.write_all(&PROTOCOL_VERSION_NUM.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut peer_magic = [0u8; 4];
+    stream.read_exact(&mut peer_magic).await?;
+    if peer_magic != PROTOCOL_MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "peer magic mismatch - not a MacWinControl peer (or an incompatible build)",
+        ));
+    }
+
+    let mut version_bytes = [0u8; 2];
+    stream.read_exact(&mut version_bytes).await?;
+    let peer_version = u16::from_be_bytes(version_bytes);
+
+    if peer_version < MIN_PROTO || peer_version > MAX_PROTO {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "peer protocol version {} outside supported range [{}, {}]",
+                peer_version, MIN_PROTO, MAX_PROTO
+            ),
+        ));
+    }
+
+    Ok(peer_version.min(PROTOCOL_VERSION_NUM))
+}
 
 pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     println!("Server listening on port {}", port);
-    
-    let clients: ClientList = Arc::new(Mutex::new(Vec::new()));
-    
+
     loop {
         let (stream, addr) = listener.accept().await?;
         println!("New connection from: {}", addr);
-        
-        let client = Arc::new(Mutex::new(stream));
-        clients.lock().await.push(client.clone());
-        
-        // Only set as ACTIVE_CLIENT if we don't already have an outgoing connection
-        // This prevents overwriting our outgoing connection with incoming ones
-        let has_outgoing = *IS_OUTGOING_CONNECTION.read().unwrap();
-        if !has_outgoing {
-            println!("üìù Using incoming connection as ACTIVE_CLIENT (no outgoing yet)");
-            *ACTIVE_CLIENT.write().unwrap() = Some(client.clone());
-        } else {
-            println!("üìù Keeping existing outgoing connection as ACTIVE_CLIENT");
-        }
-        *IS_CONNECTED.write().unwrap() = true;
-        *CONNECTED_TO.write().unwrap() = Some(addr.ip().to_string());
-        
-        let clients_clone = clients.clone();
+
+        // `IS_CONNECTED`/`CONNECTED_TO` are NOT set here - a raw TCP accept
+        // is just a socket, not an authenticated peer. They're only set
+        // once `run_server_stream` confirms the handshake and auth both
+        // succeeded, same as `WRITE_STREAM`.
         tokio::spawn(async move {
-            if let Err(e) = handle_client(client, clients_clone).await {
+            if let Err(e) = handle_client(stream, addr.ip().to_string()).await {
                 eprintln!("Client error: {}", e);
             }
         });
     }
 }
 
-async fn handle_client(
-    client: Arc<Mutex<TcpStream>>,
-    _clients: ClientList,
+async fn handle_client(mut stream: TcpStream, peer_ip: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let negotiated_version = negotiate_version(&mut stream).await?;
+    println!("🤝 Negotiated protocol version {}", negotiated_version);
+
+    let pairing_code = PAIRING_CODE.read().unwrap().clone();
+    let outcome = crypto::handshake(&mut stream, false, pairing_code.as_deref()).await?;
+    let fingerprint = crypto::fingerprint_hex(&outcome.fingerprint);
+    println!("🔐 Encrypted session established (fingerprint {})", fingerprint);
+    *CONNECTION_SECURITY.write().unwrap() = ConnectionSecurity {
+        encrypted: true,
+        peer_fingerprint: Some(fingerprint),
+        authenticated: false,
+    };
+
+    let (read_half, write_half) = stream.into_split();
+    let reader: BoxedReader = Box::new(read_half);
+    let writer: BoxedWriter = Box::new(write_half);
+    // This is the side of a TCP connection that lost (or sat out) the
+    // simultaneous-open tie-break in `should_initiate_connection` and is
+    // being dialed rather than dialing - store its write half so
+    // `send_mouse_to_remote`/`send_control_message` have somewhere to go.
+    run_server_stream(reader, writer, outcome, negotiated_version, true, true, &peer_ip).await
+}
+
+/// Accept side of one stream's worth of traffic: encrypt/frame it, challenge
+/// the peer, and (if it passes) send our `hello` and dispatch everything it
+/// sends us. Shared by plain TCP (one stream per connection) and QUIC (one
+/// call per stream - input and bulk each run this independently, each with
+/// its own handshake, since nonce counters can't safely be shared across
+/// streams). `send_hello` is `false` for QUIC's bulk stream so a peer
+/// doesn't get two redundant `hello`s for one logical connection.
+/// `store_write_stream` is `true` only for the plain-TCP accept path
+/// (`handle_client`): QUIC's two streams are opened by whichever side
+/// dialed, so there's no inbound write half to rescue there. `peer_ip` is
+/// only consulted once authentication succeeds, to populate `CONNECTED_TO`.
+async fn run_server_stream(
+    read_half: BoxedReader,
+    write_half: BoxedWriter,
+    outcome: crypto::HandshakeOutcome,
+    negotiated_version: u16,
+    send_hello: bool,
+    store_write_stream: bool,
+    peer_ip: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut buffer = vec![0u8; 4096];
-    
-    // Send hello message with screen info
-    {
-        let computer_name = get_computer_name();
-        let screens = crate::input::get_all_screens();
-        let screen_data: Vec<ScreenData> = screens.iter().map(|s| ScreenData {
-            name: s.name.clone(),
-            x: s.x,
-            y: s.y,
-            width: s.width,
-            height: s.height,
-            is_primary: s.is_primary,
-        }).collect();
-        
-        #[cfg(target_os = "macos")]
-        let computer_type = "mac";
-        #[cfg(target_os = "windows")]
-        let computer_type = "windows";
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-        let computer_type = "other";
-        
-        let hello = Message::hello_with_screens(&computer_name, screen_data, computer_type);
-        let json = serde_json::to_string(&hello)? + "\n";
-        
-        let mut stream = client.lock().await;
-        stream.write_all(json.as_bytes()).await?;
-    }
-    
-    loop {
-        let n = {
-            let mut stream = client.lock().await;
-            stream.read(&mut buffer).await?
-        };
-        
-        if n == 0 {
-            println!("Client disconnected");
-            break;
-        }
-        
-        let data = String::from_utf8_lossy(&buffer[..n]);
-        for line in data.lines() {
-            if let Ok(msg) = serde_json::from_str::<Message>(line) {
-                handle_message(&msg, &client).await?;
+    let mut writer = FramedWrite::new(write_half, MessageCodec::encrypted(outcome.send_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+    let mut reader = FramedRead::new(read_half, MessageCodec::encrypted(outcome.recv_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+
+    let authenticated = authenticate_server_side(&mut writer, &mut reader).await?;
+    if !authenticated {
+        println!("🚫 Authentication failed, dropping connection");
+        return Ok(());
+    }
+    CONNECTION_SECURITY.write().unwrap().authenticated = true;
+    // Only now - after the handshake AND the challenge/response both
+    // succeeded - is this actually an authenticated peer, so only now do
+    // `IS_CONNECTED`/`CONNECTED_TO` flip. `send_hello` doubles as "this is
+    // the once-per-logical-connection stream" (true for TCP's only stream
+    // and QUIC's input stream, false for QUIC's bulk stream), so it's
+    // reused here to avoid setting these twice for one QUIC connection.
+    if send_hello {
+        *IS_CONNECTED.write().unwrap() = true;
+        *CONNECTED_TO.write().unwrap() = Some(peer_ip.to_string());
+    }
+
+    // Rescue the writer into `WRITE_STREAM` before sending `hello` so the
+    // stored handle and the one the peer's `hello` arrives on are the same
+    // stream - see `store_write_stream`'s doc comment above. Always goes
+    // through the same Arc<Mutex<_>> wrapping as `connect_to_server` so
+    // sending never has to care which side dialed.
+    let write_arc = Arc::new(Mutex::new(writer));
+    if store_write_stream {
+        println!("📤 Setting WRITE_STREAM from inbound connection (lost simultaneous-open tie-break)");
+        *WRITE_STREAM.write().unwrap() = Some(write_arc.clone());
+        *BULK_WRITE_STREAM.write().unwrap() = None;
+    }
+
+    if send_hello {
+        write_arc.lock().await.send(local_hello()).await?;
+    }
+
+    while let Some(msg) = reader.next().await {
+        match msg {
+            Ok(msg) => dispatch_message(&msg, authenticated, negotiated_version).await?,
+            Err(e) => {
+                eprintln!("Frame decode error: {}", e);
+                break;
             }
         }
     }
-    
+
+    println!("Client disconnected");
     Ok(())
 }
 
-async fn handle_message(
-    msg: &Message, 
-    client: &Arc<Mutex<TcpStream>>
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    match msg.msg_type.as_str() {
-        "ping" => {
-            let pong = Message::pong();
-            let json = serde_json::to_string(&pong)? + "\n";
-            let mut stream = client.lock().await;
-            stream.write_all(json.as_bytes()).await?;
-        }
-        "mouse_move" => {
-            if let (Some(x), Some(y)) = (msg.x, msg.y) {
-                // Only move if we're being controlled by remote
-                let being_controlled = *BEING_CONTROLLED.read().unwrap();
-                if being_controlled {
-                    crate::input::move_mouse(x, y);
-                }
-            }
-        }
-        "mouse_click" => {
-            if let (Some(button), Some(action)) = (&msg.button, &msg.action) {
-                crate::input::mouse_click(button, action);
-            }
-        }
-        "key_event" => {
-            if let (Some(key_code), Some(action)) = (msg.key_code, &msg.action) {
-                crate::input::key_event(key_code, action);
-            }
-        }
-        "clipboard" => {
-            if let Some(text) = &msg.text {
-                let _ = crate::clipboard_sync::set_text(text);
-            }
-        }
-        "hello" => {
-            let name = msg.name.clone().unwrap_or_else(|| "Unknown".to_string());
-            let comp_type = msg.computer_type.clone().unwrap_or_else(|| "unknown".to_string());
-            println!("üì© Received hello from: {} ({})", name, comp_type);
-            
-            // Store received screens
-            if let Some(screens) = &msg.screens {
-                println!("   üì∫ Received {} screens", screens.len());
-                for s in screens {
-                    println!("      - {} {}x{} at ({},{})", s.name, s.width, s.height, s.x, s.y);
-                }
-                
-                let mut remote = REMOTE_SCREENS.write().unwrap();
-                // Remove old screens from this computer
-                remote.retain(|s| s.computer_name != name);
-                // Add new screens
-                for s in screens {
-                    remote.push(ReceivedScreen {
-                        computer_name: name.clone(),
-                        computer_type: comp_type.clone(),
-                        name: s.name.clone(),
-                        x: s.x,
-                        y: s.y,
-                        width: s.width,
-                        height: s.height,
-                        is_primary: s.is_primary,
-                    });
+/// QUIC accept-side counterpart to `handle_client`: the peer opens two
+/// bidirectional streams per connection - input first, then bulk - each
+/// negotiated and authenticated independently via `run_server_stream`.
+async fn handle_quic_connection(connection: quinn::Connection) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let peer_ip = connection.remote_address().ip().to_string();
+    let (input_send, input_recv) = connection.accept_bi().await?;
+    let mut input_reader: BoxedReader = Box::new(input_recv);
+    let mut input_writer: BoxedWriter = Box::new(input_send);
+    let input_version = {
+        let mut duplex = Duplex { reader: &mut input_reader, writer: &mut input_writer };
+        negotiate_version(&mut duplex).await?
+    };
+    let input_outcome = {
+        let mut duplex = Duplex { reader: &mut input_reader, writer: &mut input_writer };
+        let pairing_code = PAIRING_CODE.read().unwrap().clone();
+        crypto::handshake(&mut duplex, false, pairing_code.as_deref()).await?
+    };
+
+    let (bulk_send, bulk_recv) = connection.accept_bi().await?;
+    let mut bulk_reader: BoxedReader = Box::new(bulk_recv);
+    let mut bulk_writer: BoxedWriter = Box::new(bulk_send);
+    let bulk_version = {
+        let mut duplex = Duplex { reader: &mut bulk_reader, writer: &mut bulk_writer };
+        negotiate_version(&mut duplex).await?
+    };
+    let bulk_outcome = {
+        let mut duplex = Duplex { reader: &mut bulk_reader, writer: &mut bulk_writer };
+        let pairing_code = PAIRING_CODE.read().unwrap().clone();
+        crypto::handshake(&mut duplex, false, pairing_code.as_deref()).await?
+    };
+
+    let bulk_peer_ip = peer_ip.clone();
+    let bulk_task = tokio::spawn(async move {
+        run_server_stream(bulk_reader, bulk_writer, bulk_outcome, bulk_version, false, false, &bulk_peer_ip).await
+    });
+    run_server_stream(input_reader, input_writer, input_outcome, input_version, true, false, &peer_ip).await?;
+    let _ = bulk_task.await;
+    Ok(())
+}
+
+/// Listen for incoming QUIC connections and hand each off to
+/// `handle_quic_connection`. Mirrors `start_server`'s TCP accept loop.
+pub async fn start_quic_server(port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let endpoint = transport::server_endpoint(port)?;
+    println!("QUIC server listening on port {}", port);
+
+    while let Some(incoming) = endpoint.accept().await {
+        // `IS_CONNECTED`/`CONNECTED_TO` are NOT set here - accepting a QUIC
+        // handshake attempt isn't an authenticated peer yet. They're only
+        // set once `run_server_stream` confirms auth succeeded, same as
+        // the plain-TCP accept loop in `start_server`.
+        tokio::spawn(async move {
+            match incoming.await {
+                Ok(connection) => {
+                    if let Err(e) = handle_quic_connection(connection).await {
+                        eprintln!("QUIC client error: {}", e);
+                    }
                 }
-                println!("   ‚úÖ Now have {} total remote screens", remote.len());
-            } else {
-                println!("   ‚ö†Ô∏è No screens in hello message!");
-            }
-        }
-        "control_start" => {
-            // Remote is taking control of our mouse/keyboard
-            println!("üéÆ Remote is taking control!");
-            *BEING_CONTROLLED.write().unwrap() = true;
-            
-            // Move mouse to the specified position
-            if let (Some(x), Some(y)) = (msg.x, msg.y) {
-                println!("üñ±Ô∏è Moving mouse to ({}, {})", x, y);
-                // Clamp to valid screen coordinates
-                let screens = crate::input::get_all_screens();
-                let min_x = screens.iter().map(|s| s.x).min().unwrap_or(0);
-                let max_x = screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
-                let min_y = screens.iter().map(|s| s.y).min().unwrap_or(0);
-                let max_y = screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-                
-                let clamped_x = x.clamp(min_x, max_x - 1);
-                let clamped_y = y.clamp(min_y, max_y - 1);
-                
-                println!("   Screen bounds: x={}-{}, y={}-{}", min_x, max_x, min_y, max_y);
-                println!("   Clamped position: ({}, {})", clamped_x, clamped_y);
-                
-                crate::input::move_mouse(clamped_x, clamped_y);
-                
-                // Verify the move worked
-                let (actual_x, actual_y) = crate::input::get_mouse_position();
-                println!("   Actual position after move: ({}, {})", actual_x, actual_y);
+                Err(e) => eprintln!("QUIC handshake failed: {}", e),
             }
+        });
+    }
+
+    Ok(())
+}
+
+/// Run the challenge/response handshake from the accepting side. Returns
+/// `Ok(true)` once the peer has proven it holds the shared secret,
+/// `Ok(false)` if it failed (caller should close the socket without ever
+/// reaching `dispatch_message`).
+async fn authenticate_server_side(
+    writer: &mut FramedWrite<BoxedWriter, MessageCodec>,
+    reader: &mut FramedRead<BoxedReader, MessageCodec>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let nonce = auth::generate_nonce();
+    writer.send(Message::auth_challenge(nonce.clone())).await?;
+
+    let Some(Ok(Message::AuthResponse { name, hmac_hex })) = reader.next().await else {
+        println!("🚫 Expected auth_response, got something else (or nothing)");
+        return Ok(false);
+    };
+
+    let ok = auth::verify(&name, &nonce, &hmac_hex);
+    writer.send(Message::auth_status(ok)).await?;
+    Ok(ok)
+}
+
+/// One entry in `HANDLERS`. Synchronous because every registered handler
+/// below only ever touches in-process state (globals, `input`,
+/// `clipboard_sync`) - nothing here awaits, so there's no need for the
+/// boxed-future plumbing an actually-async registry would require.
+type Handler = fn(&Message);
+
+/// Registry key for a `Message` - just its variant name, since the registry
+/// only needs to route to the right handler; the handler itself pattern-
+/// matches out the fields it needs.
+fn message_kind(msg: &Message) -> &'static str {
+    match msg {
+        Message::AuthChallenge(_) => "auth_challenge",
+        Message::AuthResponse { .. } => "auth_response",
+        Message::AuthStatus { .. } => "auth_status",
+        Message::Hello { .. } => "hello",
+        Message::MouseMove { .. } => "mouse_move",
+        Message::MouseButton { .. } => "mouse_button",
+        Message::Key { .. } => "key",
+        Message::Scroll { .. } => "scroll",
+        Message::TypeText(_) => "type_text",
+        Message::CursorShape(_) => "cursor_shape",
+        Message::InputBatch(_) => "input_batch",
+        Message::ClipboardText(_) => "clipboard_text",
+        Message::ClipboardImage(_) => "clipboard_image",
+        Message::LayoutSync(_) => "layout_sync",
+        Message::ControlStart { .. } => "control_start",
+        Message::ControlEnd => "control_end",
+        Message::ScreenFrame { .. } => "screen_frame",
+        Message::PeerGossip(_) => "peer_gossip",
+        Message::ClipboardOffer { .. } => "clipboard_offer",
+        Message::ClipboardRequest { .. } => "clipboard_request",
+        Message::Heartbeat => "heartbeat",
+    }
+}
+
+/// Single table of handlers shared by both the server accept loop
+/// (`run_server_stream`) and every client read loop (`connect_to_server`,
+/// `connect_to_server_quic`), replacing the old `handle_message`/
+/// `handle_message_simple` pair - those had already drifted apart (the
+/// server clamped `ControlStart` coordinates to screen bounds, the client
+/// didn't), and every new message type used to mean remembering to update
+/// both. `Auth*` deliberately has no entry: those are consumed directly by
+/// `authenticate_server_side`/`authenticate_client_side` during the
+/// handshake and should never reach `dispatch_message`.
+static HANDLERS: Lazy<HashMap<&'static str, Handler>> = Lazy::new(|| {
+    let mut m: HashMap<&'static str, Handler> = HashMap::new();
+    m.insert("hello", handle_hello);
+    m.insert("mouse_move", handle_mouse_move);
+    m.insert("mouse_button", handle_mouse_button);
+    m.insert("key", handle_key);
+    m.insert("scroll", handle_scroll);
+    m.insert("type_text", handle_type_text);
+    m.insert("cursor_shape", handle_cursor_shape);
+    m.insert("input_batch", handle_input_batch);
+    m.insert("clipboard_text", handle_clipboard_text);
+    m.insert("clipboard_image", handle_clipboard_image);
+    m.insert("layout_sync", handle_layout_sync);
+    m.insert("control_start", handle_control_start);
+    m.insert("control_end", handle_control_end);
+    m.insert("screen_frame", handle_screen_frame);
+    m.insert("peer_gossip", handle_peer_gossip);
+    m.insert("clipboard_offer", handle_clipboard_offer);
+    m.insert("clipboard_request", handle_clipboard_request);
+    m.insert("heartbeat", handle_heartbeat);
+    m
+});
+
+/// Gate a decoded message on authentication and protocol version, then hand
+/// it to its registered handler. Shared by every read loop, server or
+/// client, TCP or QUIC - see `HANDLERS`.
+/// `authenticated` gates everything here behind the handshake in `auth` -
+/// callers only reach this loop once that handshake has already succeeded,
+/// but the flag is threaded through explicitly so dispatch can never again
+/// drift into acting on an unauthenticated socket by accident.
+async fn dispatch_message(msg: &Message, authenticated: bool, negotiated_version: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !authenticated {
+        println!("⚠️ Dropping message on unauthenticated connection: {:?}", msg);
+        return Ok(());
+    }
+    if msg.min_version() > negotiated_version {
+        println!("⚠️ Skipping {:?}: needs protocol v{}, peer negotiated v{}", msg, msg.min_version(), negotiated_version);
+        return Ok(());
+    }
+
+    match message_kind(msg) {
+        "auth_challenge" | "auth_response" | "auth_status" => {
+            println!("⚠️ Unexpected auth message after handshake completed: {:?}", msg);
         }
-        "control_end" => {
-            // Remote is releasing control
-            println!("üîì Remote released control");
-            *BEING_CONTROLLED.write().unwrap() = false;
-        }
-        "layout_sync" => {
-            // Remote is sending their screen layout
-            if let Some(layout) = &msg.layout {
-                println!("üìê Received layout sync: {}", layout);
-                *SYNCED_LAYOUT.write().unwrap() = Some(layout.clone());
+        kind => {
+            if let Some(handler) = HANDLERS.get(kind) {
+                handler(msg);
             }
         }
-        _ => {
-            println!("Unknown message type: {}", msg.msg_type);
-        }
     }
     Ok(())
 }
 
-/// Simplified message handler for client read loop (doesn't need stream reference)
-async fn handle_message_simple(msg: &Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    match msg.msg_type.as_str() {
-        "hello" => {
-            if let Some(ref name) = msg.name {
-                let comp_type = msg.computer_type.clone().unwrap_or_else(|| "unknown".to_string());
-                println!("üì© Received hello from: {} ({})", name, comp_type);
-                
-                if let Some(ref screens) = msg.screens {
-                    println!("   üì∫ Received {} screens", screens.len());
-                    let mut remote = REMOTE_SCREENS.write().unwrap();
-                    remote.retain(|s| s.computer_name != *name);
-                    for s in screens {
-                        remote.push(ReceivedScreen {
-                            computer_name: name.clone(),
-                            computer_type: comp_type.clone(),
-                            name: s.name.clone(),
-                            x: s.x,
-                            y: s.y,
-                            width: s.width,
-                            height: s.height,
-                            is_primary: s.is_primary,
-                        });
-                    }
-                    println!("   ‚úÖ Now have {} total remote screens", remote.len());
-                }
-            }
+fn handle_mouse_move(msg: &Message) {
+    if let Message::MouseMove { x, y } = msg {
+        // Only move if we're being controlled by remote
+        if *BEING_CONTROLLED.read().unwrap() {
+            crate::input::move_mouse(*x, *y);
         }
-        "control_start" => {
-            println!("üéÆ Remote is taking control!");
-            *BEING_CONTROLLED.write().unwrap() = true;
-            if let (Some(x), Some(y)) = (msg.x, msg.y) {
-                println!("üñ±Ô∏è Moving mouse to ({}, {})", x, y);
-                crate::input::move_mouse(x, y);
-            }
-        }
-        "control_end" => {
-            println!("üîì Remote released control");
-            *BEING_CONTROLLED.write().unwrap() = false;
+    }
+}
+
+fn handle_mouse_button(msg: &Message) {
+    if let Message::MouseButton { button, action } = msg {
+        crate::input::mouse_click(button, action);
+    }
+}
+
+fn handle_key(msg: &Message) {
+    if let Message::Key { key_code, action } = msg {
+        crate::input::key_event(*key_code, action);
+    }
+}
+
+fn handle_scroll(msg: &Message) {
+    if let Message::Scroll { delta_x, delta_y, precise } = msg {
+        crate::input::scroll(*delta_x, *delta_y, *precise);
+    }
+}
+
+fn handle_type_text(msg: &Message) {
+    if let Message::TypeText(text) = msg {
+        crate::input::type_text(text);
+    }
+}
+
+fn handle_cursor_shape(msg: &Message) {
+    if let Message::CursorShape(shape) = msg {
+        crate::input::set_cursor(*shape);
+    }
+}
+
+fn handle_input_batch(msg: &Message) {
+    if let Message::InputBatch(events) = msg {
+        for event in events {
+            apply_batched_input(event);
         }
-        "layout_sync" => {
-            if let Some(layout) = &msg.layout {
-                println!("üìê Received layout sync: {}", layout);
-                *SYNCED_LAYOUT.write().unwrap() = Some(layout.clone());
+    }
+}
+
+fn handle_clipboard_text(msg: &Message) {
+    if let Message::ClipboardText(text) = msg {
+        crate::clipboard_sync::apply_remote_text(text);
+    }
+}
+
+fn handle_clipboard_image(msg: &Message) {
+    if let Message::ClipboardImage(png_bytes) = msg {
+        crate::clipboard_sync::apply_remote_image(png_bytes);
+    }
+}
+
+/// Formats most recently offered by the peer via `ClipboardOffer`, for the
+/// frontend to list before deciding whether to actually pull the payload -
+/// see `get_remote_clipboard_formats`/`request_remote_clipboard`.
+static REMOTE_CLIPBOARD_FORMATS: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+fn handle_clipboard_offer(msg: &Message) {
+    if let Message::ClipboardOffer { formats } = msg {
+        println!("📋 Peer offered clipboard formats: {:?}", formats);
+        *REMOTE_CLIPBOARD_FORMATS.write().unwrap() = formats.clone();
+    }
+}
+
+/// Fulfil a peer's `ClipboardRequest` by reading that one format off our own
+/// clipboard right now and sending it back - only the format actually asked
+/// for, not whatever we most recently offered, since the clipboard may have
+/// changed again in between.
+fn handle_clipboard_request(msg: &Message) {
+    if let Message::ClipboardRequest { format } = msg {
+        let format = format.clone();
+        tokio::spawn(async move {
+            match format.as_str() {
+                "image/png" => match crate::clipboard_sync::get_image() {
+                    Ok(png) => send_to_peer(Message::clipboard_image(png)).await,
+                    Err(e) => println!("⚠️ Couldn't read clipboard image for request: {}", e),
+                },
+                "text/plain;charset=utf-8" => match crate::clipboard_sync::get_text() {
+                    Ok(text) => send_to_peer(Message::clipboard(&text)).await,
+                    Err(e) => println!("⚠️ Couldn't read clipboard text for request: {}", e),
+                },
+                other => println!("⚠️ Clipboard request for unknown format: {}", other),
             }
+        });
+    }
+}
+
+/// Formats currently offered by the connected peer (populated by the most
+/// recent `ClipboardOffer`), for the frontend to show before the user
+/// decides to pull a (possibly large) payload across.
+pub fn get_remote_clipboard_formats() -> Vec<String> {
+    REMOTE_CLIPBOARD_FORMATS.read().unwrap().clone()
+}
+
+/// Ask the peer to actually send the payload for one of its offered
+/// formats - the lazy half of `start_clipboard_sync`'s image handling.
+pub async fn request_remote_clipboard(format: &str) {
+    send_to_peer(Message::clipboard_request(format)).await;
+}
+
+fn handle_hello(msg: &Message) {
+    if let Message::Hello { name, computer_type, screens, .. } = msg {
+        if !check_peer_identity(name) {
+            return;
         }
-        "mouse_move" => {
-            if let (Some(x), Some(y)) = (msg.x, msg.y) {
-                // Only move if we're being controlled by remote
-                let being_controlled = *BEING_CONTROLLED.read().unwrap();
-                if being_controlled {
-                    crate::input::move_mouse(x, y);
+        println!("📩 Received hello from: {} ({})", name, computer_type);
+        store_remote_screens(name, computer_type, screens);
+    }
+}
+
+fn handle_control_start(msg: &Message) {
+    if let Message::ControlStart { x, y } = msg {
+        // Remote is taking control of our mouse/keyboard
+        println!("🎮 Remote is taking control!");
+        *BEING_CONTROLLED.write().unwrap() = true;
+
+        // Clamp to valid screen coordinates - a peer (malicious or just
+        // confused about our layout) could otherwise hand us a position
+        // off every display.
+        let screens = crate::input::get_all_screens();
+        let min_x = screens.iter().map(|s| s.x).min().unwrap_or(0);
+        let max_x = screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
+        let min_y = screens.iter().map(|s| s.y).min().unwrap_or(0);
+        let max_y = screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
+
+        let clamped_x = x.clamp(min_x, max_x - 1);
+        let clamped_y = y.clamp(min_y, max_y - 1);
+
+        println!("   Screen bounds: x={}-{}, y={}-{}", min_x, max_x, min_y, max_y);
+        println!("   Clamped position: ({}, {})", clamped_x, clamped_y);
+
+        crate::input::move_mouse(clamped_x, clamped_y);
+
+        let (actual_x, actual_y) = crate::input::get_mouse_position();
+        println!("   Actual position after move: ({}, {})", actual_x, actual_y);
+    }
+}
+
+fn handle_control_end(_msg: &Message) {
+    println!("🔓 Remote released control");
+    *BEING_CONTROLLED.write().unwrap() = false;
+}
+
+fn handle_layout_sync(msg: &Message) {
+    if let Message::LayoutSync(layout) = msg {
+        println!("📐 Received layout sync: {}", layout);
+        *SYNCED_LAYOUT.write().unwrap() = Some(layout.clone());
+    }
+}
+
+fn handle_screen_frame(msg: &Message) {
+    if let Message::ScreenFrame { frame_width, frame_height, x, y, width, height, jpeg } = msg {
+        crate::screen_stream::emit_remote_frame(*frame_width, *frame_height, (*x, *y, *width, *height), jpeg);
+    }
+}
+
+fn handle_heartbeat(_msg: &Message) {}
+
+fn handle_peer_gossip(msg: &Message) {
+    if let Message::PeerGossip(peers) = msg {
+        merge_gossiped_peers(peers);
+    }
+}
+
+/// Merge a peer's gossiped table into `DISCOVERED_PEERS`, keeping whichever
+/// side (ours or theirs) has the newer `last_seen` for each IP - the same
+/// last-writer-wins rule a CRDS table uses, so repeated gossip from several
+/// peers about the same host converges instead of flapping. Never touches
+/// `key`/`nonce`/`proto_ver`: those only mean something to a peer we've
+/// actually talked to directly, not one we merely heard about.
+fn merge_gossiped_peers(gossiped: &[GossipPeer]) {
+    let local_ip = local_ip_address::local_ip()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|_| "0.0.0.0".to_string());
+
+    let mut peers = DISCOVERED_PEERS.write().unwrap();
+    for gossiped_peer in gossiped {
+        if gossiped_peer.ip == local_ip {
+            continue;
+        }
+        match peers.iter_mut().find(|p| p.ip == gossiped_peer.ip) {
+            Some(existing) => {
+                if gossiped_peer.last_seen > existing.last_seen {
+                    existing.name = gossiped_peer.name.clone();
+                    existing.computer_type = gossiped_peer.computer_type.clone();
+                    existing.last_seen = gossiped_peer.last_seen;
                 }
             }
+            None => peers.push(DiscoveredPeer {
+                name: gossiped_peer.name.clone(),
+                ip: gossiped_peer.ip.clone(),
+                computer_type: gossiped_peer.computer_type.clone(),
+                last_seen: gossiped_peer.last_seen,
+                key: None,
+                proto_ver: MIN_PROTO,
+                nonce: 0,
+            }),
         }
-        "mouse_click" => {
-            if let (Some(button), Some(action)) = (&msg.button, &msg.action) {
-                crate::input::mouse_click(button, action);
+    }
+}
+
+/// Verify (and, the first time, pin) the handshake key behind a `hello`'s
+/// claimed `name` against `DISCOVERED_PEERS`, closing the spoofing gap where
+/// any host on the LAN could claim another machine's `computer_name` and
+/// overwrite its `REMOTE_SCREENS` entry. Returns `false` - logging a warning
+/// instead of storing anything - if `name` is already pinned to a different
+/// key than the one this connection's handshake produced.
+fn check_peer_identity(name: &str) -> bool {
+    let Some(fingerprint) = CONNECTION_SECURITY.read().unwrap().peer_fingerprint.clone() else {
+        // Dispatch only runs once `authenticated` is true, which implies a
+        // completed handshake and therefore a fingerprint - this is just
+        // defensive since there's nothing to pin against without one.
+        return true;
+    };
+
+    let mut peers = DISCOVERED_PEERS.write().unwrap();
+    match peers.iter_mut().find(|p| p.name == name) {
+        Some(peer) => match &peer.key {
+            Some(known) if *known != fingerprint => {
+                println!(
+                    "🚫 Refusing hello from '{}': key {} doesn't match previously pinned {}",
+                    name, fingerprint, known
+                );
+                false
             }
-        }
-        "key_event" => {
-            if let (Some(key_code), Some(action)) = (msg.key_code, &msg.action) {
-                crate::input::key_event(key_code, action);
+            Some(_) => true,
+            None => {
+                peer.key = Some(fingerprint);
+                true
+            }
+        },
+        // Not seen via UDP discovery yet (e.g. a direct connect) - nothing
+        // to pin against, so let it through; it gets pinned by the first
+        // `hello` after discovery adds an entry for this name.
+        None => true,
+    }
+}
+
+/// Replay one event out of an `Message::InputBatch`, the same way its
+/// standalone `Message` variant is handled above - shared by both dispatch
+/// functions since there's only one sensible way to apply a batched event.
+fn apply_batched_input(event: &BatchedInput) {
+    match event {
+        BatchedInput::MouseMove { x, y } => {
+            if *BEING_CONTROLLED.read().unwrap() {
+                crate::input::move_mouse(*x, *y);
             }
         }
-        _ => {
-            println!("Unknown message type: {}", msg.msg_type);
-        }
+        BatchedInput::MouseButton { button, action } => crate::input::mouse_click(button, action),
+        BatchedInput::Key { key_code, action } => crate::input::key_event(*key_code, action),
+        BatchedInput::Scroll { delta_x, delta_y, precise } => crate::input::scroll(*delta_x, *delta_y, *precise),
+        BatchedInput::TypeText(text) => crate::input::type_text(text),
     }
-    Ok(())
 }
 
-pub async fn connect_to_server(ip: &str, port: u16) -> Result<Arc<Mutex<TcpStream>>, Box<dyn std::error::Error + Send + Sync>> {
-    let stream = TcpStream::connect(format!("{}:{}", ip, port)).await?;
-    println!("Connected to {}:{}", ip, port);
-    
+fn store_remote_screens(name: &str, computer_type: &str, screens: &[ScreenData]) {
+    println!("   📺 Received {} screens", screens.len());
+    let mut remote = REMOTE_SCREENS.write().unwrap();
+    remote.retain(|s| s.computer_name != name);
+    for s in screens {
+        remote.push(ReceivedScreen {
+            computer_name: name.to_string(),
+            computer_type: computer_type.to_string(),
+            name: s.name.clone(),
+            x: s.x,
+            y: s.y,
+            width: s.width,
+            height: s.height,
+            is_primary: s.is_primary,
+        });
+    }
+    println!("   ✅ Now have {} total remote screens", remote.len());
+}
+
+fn local_hello() -> Message {
+    let computer_name = get_computer_name();
+    let screens = crate::input::get_all_screens();
+    let screen_data: Vec<ScreenData> = screens.iter().map(|s| ScreenData {
+        name: s.name.clone(),
+        x: s.x,
+        y: s.y,
+        width: s.width,
+        height: s.height,
+        is_primary: s.is_primary,
+    }).collect();
+
+    Message::hello_with_screens(&computer_name, screen_data, get_computer_type())
+}
+
+pub async fn connect_to_server(ip: &str, port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut stream = TcpStream::connect(format!("{}:{}", ip, port)).await?;
+    println!("Connected to {}:{}", ip, port);
+
+    let negotiated_version = negotiate_version(&mut stream).await?;
+    println!("🤝 Negotiated protocol version {}", negotiated_version);
+
+    let pairing_code = PAIRING_CODE.read().unwrap().clone();
+    let outcome = crypto::handshake(&mut stream, true, pairing_code.as_deref()).await?;
+    let fingerprint = crypto::fingerprint_hex(&outcome.fingerprint);
+    println!("🔐 Encrypted session established (fingerprint {})", fingerprint);
+    *CONNECTION_SECURITY.write().unwrap() = ConnectionSecurity {
+        encrypted: true,
+        peer_fingerprint: Some(fingerprint),
+        authenticated: false,
+    };
+
     // Split the stream into read and write halves
     let (read_half, write_half) = stream.into_split();
-    
-    // Store write half for sending messages (non-blocking!)
-    let write_arc = Arc::new(Mutex::new(write_half));
-    println!("üì§ Setting WRITE_STREAM for sending messages");
+    let read_half: BoxedReader = Box::new(read_half);
+    let write_half: BoxedWriter = Box::new(write_half);
+
+    let mut writer = FramedWrite::new(write_half, MessageCodec::encrypted(outcome.send_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+    let mut reader = FramedRead::new(read_half, MessageCodec::encrypted(outcome.recv_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+
+    if !authenticate_client_side(&mut writer, &mut reader).await? {
+        return Err("authentication rejected by server".into());
+    }
+    CONNECTION_SECURITY.write().unwrap().authenticated = true;
+
+    // Store write half for sending messages (non-blocking!). TCP has only
+    // the one stream, so bulk traffic shares it too - see `send_to_peer`.
+    let write_arc = Arc::new(Mutex::new(writer));
+    println!("📤 Setting WRITE_STREAM for sending messages");
     *WRITE_STREAM.write().unwrap() = Some(write_arc.clone());
-    *IS_OUTGOING_CONNECTION.write().unwrap() = true;
-    
+    *BULK_WRITE_STREAM.write().unwrap() = None;
+
     // Send hello with screen info using the write half
     {
-        let computer_name = get_computer_name();
-        let screens = crate::input::get_all_screens();
-        let screen_data: Vec<ScreenData> = screens.iter().map(|s| ScreenData {
-            name: s.name.clone(),
-            x: s.x,
-            y: s.y,
-            width: s.width,
-            height: s.height,
-            is_primary: s.is_primary,
-        }).collect();
-        
-        #[cfg(target_os = "macos")]
-        let computer_type = "mac";
-        #[cfg(target_os = "windows")]
-        let computer_type = "windows";
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-        let computer_type = "other";
-        
-        let hello = Message::hello_with_screens(&computer_name, screen_data, computer_type);
-        let json = serde_json::to_string(&hello)? + "\n";
-        
         let mut writer = write_arc.lock().await;
-        writer.write_all(json.as_bytes()).await?;
+        writer.send(local_hello()).await?;
     }
-    
+
     // Start client read loop to receive messages from server (uses read half only)
     tokio::spawn(async move {
-        let mut reader = BufReader::new(read_half);
-        let mut line = String::new();
         loop {
-            line.clear();
-            match reader.read_line(&mut line).await {
-                Ok(0) => {
+            match reader.next().await {
+                Some(Ok(msg)) => {
+                    if let Err(e) = dispatch_message(&msg, true, negotiated_version).await {
+                        eprintln!("Error handling message: {}", e);
+                    }
+                }
+                Some(Err(e)) => {
+                    eprintln!("Frame decode error: {}", e);
+                    break;
+                }
+                None => {
                     println!("Disconnected from server");
                     break;
                 }
-                Ok(_) => {
-                    if let Ok(msg) = serde_json::from_str::<Message>(&line) {
-                        // Create a dummy client for handle_message (not used for control_start)
-                        let dummy = Arc::new(Mutex::new(TcpStream::connect("127.0.0.1:1").await.ok()));
-                        // We can't easily pass the stream here, but handle_message for received messages
-                        // doesn't need to write back for control_start - it just calls move_mouse
-                        if let Err(e) = handle_message_simple(&msg).await {
-                            eprintln!("Error handling message: {}", e);
-                        }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// QUIC dialing counterpart to `connect_to_server`: opens two independent
+/// bidirectional streams - `input` (stored as `WRITE_STREAM`) and `bulk`
+/// (stored as `BULK_WRITE_STREAM`) - each with its own version negotiation,
+/// crypto handshake, and auth challenge, then spawns a read loop per stream.
+pub async fn connect_to_server_quic(ip: &str, port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let endpoint = transport::client_endpoint()?;
+    let connection = endpoint.connect(format!("{}:{}", ip, port).parse()?, "macwincontrol.local")?.await?;
+    println!("Connected (QUIC) to {}:{}", ip, port);
+
+    let (input_writer, input_reader, negotiated_version) = open_quic_client_stream(&connection).await?;
+    let (bulk_writer, bulk_reader, _bulk_version) = open_quic_client_stream(&connection).await?;
+
+    let write_arc = Arc::new(Mutex::new(input_writer));
+    let bulk_arc = Arc::new(Mutex::new(bulk_writer));
+    println!("📤 Setting WRITE_STREAM/BULK_WRITE_STREAM for sending messages (QUIC)");
+    *WRITE_STREAM.write().unwrap() = Some(write_arc.clone());
+    *BULK_WRITE_STREAM.write().unwrap() = Some(bulk_arc);
+
+    {
+        let mut writer = write_arc.lock().await;
+        writer.send(local_hello()).await?;
+    }
+
+    spawn_quic_read_loop(input_reader, negotiated_version);
+    spawn_quic_read_loop(bulk_reader, negotiated_version);
+
+    Ok(())
+}
+
+/// Open one QUIC bidirectional stream, run version negotiation, the crypto
+/// handshake, and the auth challenge over it, and return the framed
+/// reader/writer ready for application traffic.
+async fn open_quic_client_stream(
+    connection: &quinn::Connection,
+) -> Result<(FramedWrite<BoxedWriter, MessageCodec>, FramedRead<BoxedReader, MessageCodec>, u16), Box<dyn std::error::Error + Send + Sync>> {
+    let (send, recv) = connection.open_bi().await?;
+    let mut reader: BoxedReader = Box::new(recv);
+    let mut writer: BoxedWriter = Box::new(send);
+
+    let negotiated_version = {
+        let mut duplex = Duplex { reader: &mut reader, writer: &mut writer };
+        negotiate_version(&mut duplex).await?
+    };
+    let outcome = {
+        let mut duplex = Duplex { reader: &mut reader, writer: &mut writer };
+        let pairing_code = PAIRING_CODE.read().unwrap().clone();
+        crypto::handshake(&mut duplex, true, pairing_code.as_deref()).await?
+    };
+    let fingerprint = crypto::fingerprint_hex(&outcome.fingerprint);
+    *CONNECTION_SECURITY.write().unwrap() = ConnectionSecurity { encrypted: true, peer_fingerprint: Some(fingerprint), authenticated: false };
+
+    let mut framed_writer = FramedWrite::new(writer, MessageCodec::encrypted(outcome.send_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+    let mut framed_reader = FramedRead::new(reader, MessageCodec::encrypted(outcome.recv_cipher).with_max_frame(SCREEN_STREAM_MAX_FRAME));
+
+    if !authenticate_client_side(&mut framed_writer, &mut framed_reader).await? {
+        return Err("authentication rejected by server".into());
+    }
+    CONNECTION_SECURITY.write().unwrap().authenticated = true;
+
+    Ok((framed_writer, framed_reader, negotiated_version))
+}
+
+fn spawn_quic_read_loop(mut reader: FramedRead<BoxedReader, MessageCodec>, negotiated_version: u16) {
+    tokio::spawn(async move {
+        loop {
+            match reader.next().await {
+                Some(Ok(msg)) => {
+                    if let Err(e) = dispatch_message(&msg, true, negotiated_version).await {
+                        eprintln!("Error handling message: {}", e);
                     }
                 }
-                Err(e) => {
-                    eprintln!("Read error: {}", e);
+                Some(Err(e)) => {
+                    eprintln!("Frame decode error: {}", e);
+                    break;
+                }
+                None => {
+                    println!("Disconnected from server (QUIC)");
                     break;
                 }
             }
         }
     });
-    
-    // Return a dummy Arc for compatibility (not used anymore)
-    let dummy_stream = TcpStream::connect(format!("{}:{}", ip, port)).await?;
-    Ok(Arc::new(Mutex::new(dummy_stream)))
 }
 
-pub async fn send_message(client: &Arc<Mutex<TcpStream>>, msg: &Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let json = serde_json::to_string(msg)? + "\n";
-    let mut stream = client.lock().await;
-    stream.write_all(json.as_bytes()).await?;
-    Ok(())
+/// Run the challenge/response handshake from the dialing side. Returns
+/// `Ok(true)` once the server has confirmed our HMAC response.
+async fn authenticate_client_side(
+    writer: &mut FramedWrite<BoxedWriter, MessageCodec>,
+    reader: &mut FramedRead<BoxedReader, MessageCodec>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(Ok(Message::AuthChallenge(nonce))) = reader.next().await else {
+        return Err("expected auth_challenge, got something else (or nothing)".into());
+    };
+
+    let my_name = get_computer_name();
+    let Some(hmac_hex) = auth::respond(&my_name, &nonce) else {
+        return Err("no shared secret configured (set MACWINCONTROL_SHARED_SECRET)".into());
+    };
+    writer.send(Message::auth_response(&my_name, hmac_hex)).await?;
+
+    match reader.next().await {
+        Some(Ok(Message::AuthStatus { ok })) => Ok(ok),
+        _ => Err("expected auth_status, got something else (or nothing)".into()),
+    }
 }
 
 fn get_computer_name() -> String {
@@ -624,17 +1284,28 @@ pub async fn start_auto_discovery() -> Result<(), Box<dyn std::error::Error + Se
     let local_ip = local_ip_address::local_ip()
         .map(|ip| ip.to_string())
         .unwrap_or_else(|_| "0.0.0.0".to_string());
-    
-    println!("üöÄ Starting MacWinControl auto-discovery...");
-    println!("üìç Local IP: {}", local_ip);
-    
-    // Start TCP server
-    tokio::spawn(async {
-        if let Err(e) = start_server(TCP_PORT).await {
-            eprintln!("TCP server error: {}", e);
+
+    println!("🚀 Starting MacWinControl auto-discovery...");
+    println!("📍 Local IP: {}", local_ip);
+
+    // Start the server side for whichever transport is configured
+    match transport::get_transport_kind() {
+        transport::TransportKind::Tcp => {
+            tokio::spawn(async {
+                if let Err(e) = start_server(TCP_PORT).await {
+                    eprintln!("TCP server error: {}", e);
+                }
+            });
         }
-    });
-    
+        transport::TransportKind::Quic => {
+            tokio::spawn(async {
+                if let Err(e) = start_quic_server(transport::QUIC_PORT).await {
+                    eprintln!("QUIC server error: {}", e);
+                }
+            });
+        }
+    }
+
     // Start UDP broadcaster (announce our presence)
     let local_ip_clone = local_ip.clone();
     tokio::spawn(async move {
@@ -642,7 +1313,7 @@ pub async fn start_auto_discovery() -> Result<(), Box<dyn std::error::Error + Se
             eprintln!("UDP broadcaster error: {}", e);
         }
     });
-    
+
     // Start UDP listener (discover peers)
     let local_ip_clone2 = local_ip.clone();
     tokio::spawn(async move {
@@ -650,38 +1321,231 @@ pub async fn start_auto_discovery() -> Result<(), Box<dyn std::error::Error + Se
             eprintln!("UDP listener error: {}", e);
         }
     });
-    
+
     // Start mouse tracking for edge detection
     tokio::spawn(async {
         start_mouse_tracking().await;
     });
-    
+
+    // Start clipboard sync - watches the local clipboard and forwards
+    // changes to the connected peer (no-op until one connects).
+    start_clipboard_sync();
+
+    // Start the input batcher - coalesces queued moves/scrolls/clicks/keys
+    // into periodic `InputBatch` sends instead of one write per event.
+    start_input_batching();
+
+    // Gossip our peer table to whoever we're connected to, and prune
+    // entries nobody's refreshed in a while - see `start_peer_gossip` and
+    // `start_peer_pruning`.
+    start_peer_gossip();
+    start_peer_pruning();
+
     Ok(())
 }
 
+/// Bridge the (synchronous, thread-based) clipboard watcher into the async
+/// world: every detected change is forwarded to the connected peer, gated on
+/// `CLIPBOARD_SYNC_ENABLED` - text as a `ClipboardText` message, an image only
+/// as a `ClipboardOffer` the peer can follow up with a `ClipboardRequest`.
+fn start_clipboard_sync() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<crate::clipboard_sync::ClipboardContent>();
+
+    crate::clipboard_sync::watch_clipboard(move |content| {
+        let _ = tx.send(content);
+    });
+
+    tokio::spawn(async move {
+        while let Some(content) = rx.recv().await {
+            if !*CLIPBOARD_SYNC_ENABLED.read().unwrap() {
+                continue;
+            }
+            match content {
+                crate::clipboard_sync::ClipboardContent::Text(text) => {
+                    // Cheap - send it straight away rather than making the
+                    // peer round-trip a request for it.
+                    send_to_peer(Message::clipboard(&text)).await;
+                }
+                crate::clipboard_sync::ClipboardContent::Image(_png) => {
+                    // Images can be large, so only offer the format; the
+                    // peer pulls the actual bytes with a `ClipboardRequest`
+                    // if/when it wants them - see `handle_clipboard_request`.
+                    send_to_peer(Message::clipboard_offer(vec!["image/png".to_string()])).await;
+                }
+            }
+        }
+    });
+}
+
+/// Enable or disable forwarding local clipboard changes to the peer.
+pub fn set_clipboard_sync_enabled(enabled: bool) {
+    *CLIPBOARD_SYNC_ENABLED.write().unwrap() = enabled;
+}
+
+/// Bridge the (synchronous, OS-tap/hook-based) input capture into the async
+/// world: every local mouse button/scroll/key event swallowed while we're
+/// the controller (see `input::start_capture`) is forwarded to the remote
+/// with the same `send_*_to_remote` calls the frontend would otherwise use.
+/// Mouse motion isn't included here - `start_mouse_tracking`'s edge-follow
+/// polling already drives `send_mouse_to_remote` from position deltas.
+fn start_input_capture() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<crate::input::InputEvent>();
+
+    crate::input::start_capture(move |event| {
+        let _ = tx.send(event);
+    });
+
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if !*CONTROL_ACTIVE.read().unwrap() {
+                continue;
+            }
+            match event {
+                crate::input::InputEvent::MouseButton { button, action } => {
+                    send_click_to_remote(&button, &action).await;
+                }
+                crate::input::InputEvent::Scroll { delta_x, delta_y } => {
+                    send_scroll_to_remote(delta_x, delta_y, false).await;
+                }
+                crate::input::InputEvent::Key { key_code, action } => {
+                    send_key_to_remote(key_code, &action).await;
+                }
+            }
+        }
+    });
+}
+
+/// Queue an input event for the next `InputBatch` flush rather than sending
+/// it immediately - see `start_input_batching`. Events are replayed in the
+/// order they were queued, so callers don't need to worry about reordering
+/// a click relative to the moves around it.
+async fn queue_batched_input(event: BatchedInput) {
+    INPUT_BATCH.lock().await.push(event);
+}
+
+/// Every `INPUT_BATCH_INTERVAL`, drain whatever's queued in `INPUT_BATCH` and
+/// ship it as a single `Message::InputBatch`. Idle ticks (nothing queued)
+/// send nothing.
+fn start_input_batching() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(INPUT_BATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let batch = {
+                let mut queue = INPUT_BATCH.lock().await;
+                std::mem::take(&mut *queue)
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            send_to_peer(Message::input_batch(batch)).await;
+        }
+    });
+}
+
+/// Every `PEER_GOSSIP_INTERVAL`, relay our known-peer table to the connected
+/// peer (a no-op send if there isn't one) so peers on a different subnet -
+/// unreachable by our UDP broadcasts - become discoverable transitively
+/// through whatever TCP link does exist. See `handle_peer_gossip`.
+fn start_peer_gossip() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(PEER_GOSSIP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let table: Vec<GossipPeer> = DISCOVERED_PEERS
+                .read()
+                .unwrap()
+                .iter()
+                .map(|p| GossipPeer {
+                    name: p.name.clone(),
+                    ip: p.ip.clone(),
+                    computer_type: p.computer_type.clone(),
+                    last_seen: p.last_seen,
+                })
+                .collect();
+            if table.is_empty() {
+                continue;
+            }
+            send_to_peer(Message::peer_gossip(table)).await;
+        }
+    });
+}
+
+/// Every `PEER_GOSSIP_INTERVAL`, drop any `DiscoveredPeer` not refreshed
+/// within `PEER_STALE_TIMEOUT` - otherwise a host taken off the network
+/// stays in `DISCOVERED_PEERS` (and the UI's peer list) forever, since UDP
+/// discovery only ever added/refreshed entries.
+fn start_peer_pruning() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(PEER_GOSSIP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let timeout = PEER_STALE_TIMEOUT.as_secs();
+            DISCOVERED_PEERS.write().unwrap().retain(|p| now.saturating_sub(p.last_seen) <= timeout);
+        }
+    });
+}
+
+/// Resolve a simultaneous-open race the way multistream-select does: compare
+/// the `(ip, nonce)` tuple each side broadcasts, and let the numerically
+/// larger one be the sole initiator. The other side must not dial - it
+/// drops its own attempt and waits for the inbound connection instead (see
+/// the `store_write_stream` side of `handle_client`). IPs that fail to
+/// parse as IPv4 (unexpected on this LAN-only discovery path) count as 0;
+/// equal IP keys fall through to the nonce and then to a lexical compare of
+/// the IP strings, so the tie-break still resolves to *something*
+/// consistent on both sides.
+fn should_initiate_connection(peer_ip: &str, peer_nonce: u64) -> bool {
+    let local_ip = local_ip_address::local_ip()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|_| "0.0.0.0".to_string());
+
+    let local_key = local_ip.parse::<std::net::Ipv4Addr>().map(u32::from).unwrap_or(0);
+    let peer_key = peer_ip.parse::<std::net::Ipv4Addr>().map(u32::from).unwrap_or(0);
+
+    match local_key.cmp(&peer_key) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        // Same IP tuple (shouldn't happen - two peers on one address) or
+        // both unparseable: fall through to the nonce, then to the raw IP
+        // string so there's always a deterministic winner.
+        std::cmp::Ordering::Equal => match (*LOCAL_NONCE).cmp(&peer_nonce) {
+            std::cmp::Ordering::Equal => local_ip > peer_ip.to_string(),
+            ord => ord == std::cmp::Ordering::Greater,
+        },
+    }
+}
+
 /// Broadcast our presence every 2 seconds
 async fn start_udp_broadcaster(local_ip: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let socket = UdpSocket::bind("0.0.0.0:0").await?;
     socket.set_broadcast(true)?;
-    
+
     let computer_name = get_computer_name();
     let computer_type = get_computer_type();
-    
-    // Broadcast message format: MACWINCTRL|name|ip|type
-    let message = format!("{}|{}|{}|{}", DISCOVERY_MAGIC, computer_name, local_ip, computer_type);
-    
-    println!("üì¢ Broadcasting presence: {}", message);
-    
+
+    // Broadcast message format: MACWINCTRL|name|ip|type|proto_ver|nonce
+    let message = format!(
+        "{}|{}|{}|{}|{}|{}",
+        DISCOVERY_MAGIC, computer_name, local_ip, computer_type, PROTOCOL_VERSION_NUM, *LOCAL_NONCE
+    );
+
+    println!("📢 Broadcasting presence: {}", message);
+
     loop {
         // Broadcast to 255.255.255.255
         let _ = socket.send_to(message.as_bytes(), format!("255.255.255.255:{}", UDP_PORT)).await;
-        
+
         // Also try common subnet broadcasts
         if let Some(subnet) = local_ip.rsplit_once('.') {
             let broadcast_ip = format!("{}.255", subnet.0);
             let _ = socket.send_to(message.as_bytes(), format!("{}:{}", broadcast_ip, UDP_PORT)).await;
         }
-        
+
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
     }
 }
@@ -689,64 +1553,100 @@ async fn start_udp_broadcaster(local_ip: &str) -> Result<(), Box<dyn std::error:
 /// Listen for UDP broadcasts from other peers
 async fn start_udp_listener(local_ip: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let socket = UdpSocket::bind(format!("0.0.0.0:{}", UDP_PORT)).await?;
-    println!("üëÇ Listening for peers on UDP port {}", UDP_PORT);
-    
+    println!("👂 Listening for peers on UDP port {}", UDP_PORT);
+
     let mut buffer = [0u8; 1024];
-    
+
     loop {
         let (len, addr) = socket.recv_from(&mut buffer).await?;
         let message = String::from_utf8_lossy(&buffer[..len]);
-        
-        // Parse: MACWINCTRL|name|ip|type
+
+        // Parse: MACWINCTRL|name|ip|type|proto_ver|nonce
         let parts: Vec<&str> = message.split('|').collect();
         if parts.len() >= 4 && parts[0] == DISCOVERY_MAGIC {
             let peer_name = parts[1].to_string();
             let peer_ip = parts[2].to_string();
             let peer_type = parts[3].to_string();
-            
+            // Older builds broadcast without trailing proto_ver/nonce fields;
+            // treat those as MIN_PROTO and a nonce of 0 (the nonce only
+            // decides the tie-break when both IPs compare equal, where 0
+            // loses to any non-zero local nonce).
+            let peer_proto_ver = parts
+                .get(4)
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or(MIN_PROTO);
+            let peer_nonce = parts.get(5).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+
             // Ignore our own broadcasts
             if peer_ip == local_ip {
                 continue;
             }
-            
-            println!("üîç Discovered peer: {} ({}) at {}", peer_name, peer_type, peer_ip);
-            
+
+            println!("🔍 Discovered peer: {} ({}) at {}", peer_name, peer_type, peer_ip);
+
             // Update discovered peers list
             let now = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs();
-            
+
             {
                 let mut peers = DISCOVERED_PEERS.write().unwrap();
                 if let Some(existing) = peers.iter_mut().find(|p| p.ip == peer_ip) {
                     existing.last_seen = now;
+                    existing.proto_ver = peer_proto_ver;
+                    existing.nonce = peer_nonce;
                 } else {
                     peers.push(DiscoveredPeer {
                         name: peer_name.clone(),
                         ip: peer_ip.clone(),
                         computer_type: peer_type.clone(),
                         last_seen: now,
+                        key: None,
+                        proto_ver: peer_proto_ver,
+                        nonce: peer_nonce,
                     });
                 }
             }
-            
-            // Auto-connect if we don't have a write stream yet
-            // (incoming connections don't give us a write stream for sending)
+
+            if peer_proto_ver < MIN_PROTO || peer_proto_ver > MAX_PROTO {
+                println!(
+                    "⚠️ {} advertised protocol v{}, outside supported range [{}, {}] - not auto-connecting",
+                    peer_ip, peer_proto_ver, MIN_PROTO, MAX_PROTO
+                );
+                continue;
+            }
+
+            // Auto-connect if we don't have a write stream yet, and we'd win
+            // the simultaneous-open tie-break against this peer. Losing the
+            // tie-break means the peer is expected to dial us instead - see
+            // `should_initiate_connection` and `handle_client`'s
+            // `store_write_stream`.
             let has_write_stream = WRITE_STREAM.read().unwrap().is_some();
+            if !has_write_stream && !should_initiate_connection(&peer_ip, peer_nonce) {
+                println!(
+                    "⏳ Lost simultaneous-open tie-break with {} - waiting to be dialed instead",
+                    peer_ip
+                );
+                continue;
+            }
             if !has_write_stream {
-                println!("üîó Auto-connecting to {}...", peer_ip);
-                
+                println!("🔗 Auto-connecting to {}...", peer_ip);
+
                 let peer_ip_clone = peer_ip.clone();
                 tokio::spawn(async move {
-                    match connect_to_server(&peer_ip_clone, TCP_PORT).await {
+                    let result = match transport::get_transport_kind() {
+                        transport::TransportKind::Tcp => connect_to_server(&peer_ip_clone, TCP_PORT).await,
+                        transport::TransportKind::Quic => connect_to_server_quic(&peer_ip_clone, transport::QUIC_PORT).await,
+                    };
+                    match result {
                         Ok(_) => {
-                            println!("‚úÖ Connected to {}", peer_ip_clone);
+                            println!("✅ Connected to {}", peer_ip_clone);
                             *IS_CONNECTED.write().unwrap() = true;
                             *CONNECTED_TO.write().unwrap() = Some(peer_ip_clone);
                         }
                         Err(e) => {
-                            println!("‚ùå Failed to connect: {}", e);
+                            println!("❌ Failed to connect: {}", e);
                         }
                     }
                 });
@@ -775,34 +1675,46 @@ pub fn get_debug_info() -> DebugInfo {
     DEBUG_INFO.read().unwrap().clone()
 }
 
+/// Whether the active link is encrypted, and the peer's key fingerprint for
+/// out-of-band verification.
+pub fn get_connection_security() -> ConnectionSecurity {
+    CONNECTION_SECURITY.read().unwrap().clone()
+}
+
+/// Set the pairing code both peers must enter for the handshake to derive a
+/// shared key. Pass `None` to go back to ECDH-only (no MITM protection).
+pub fn set_pairing_code(code: Option<String>) {
+    *PAIRING_CODE.write().unwrap() = code;
+}
+
 // ============= MOUSE TRACKING & EDGE DETECTION =============
 
 /// Start mouse tracking - monitors mouse position and handles edge transitions
 pub async fn start_mouse_tracking() {
-    println!("üñ±Ô∏è Starting mouse tracking...");
-    
+    println!("🖱️ Starting mouse tracking...");
+
     let mut last_pos = (0i32, 0i32);
     let edge_threshold = 10;  // pixels from edge to trigger transition (increased for macOS)
     let mut debug_counter = 0u32;
     let mut loop_counter = 0u64;
-    
+
     loop {
         tokio::time::sleep(tokio::time::Duration::from_millis(8)).await;  // ~125 Hz for lower latency
-        
+
         loop_counter += 1;
-        
+
         // Read all state upfront to avoid holding locks across await
         let is_connected = *IS_CONNECTED.read().unwrap();
         let being_controlled = *BEING_CONTROLLED.read().unwrap();
         let control_active = *CONTROL_ACTIVE.read().unwrap();
-        
+
         let (mx, my) = crate::input::get_mouse_position();
-        
+
         // Log every 5 seconds to verify loop is running
         if loop_counter % 300 == 0 {
-            println!("üîÑ Mouse tracking alive: pos=({},{}) connected={}", mx, my, is_connected);
+            println!("🔄 Mouse tracking alive: pos=({},{}) connected={}", mx, my, is_connected);
         }
-        
+
         // Update debug info every ~0.5 seconds (every 30 iterations at 60Hz)
         debug_counter += 1;
         if debug_counter >= 30 {
@@ -812,7 +1724,7 @@ pub async fn start_mouse_tracking() {
             let total_max_x = screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
             let total_min_y = screens.iter().map(|s| s.y).min().unwrap_or(0);
             let total_max_y = screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-            
+
             let edge_status = if !is_connected {
                 "Not connected".to_string()
             } else if being_controlled {
@@ -827,63 +1739,74 @@ pub async fn start_mouse_tracking() {
                     my >= total_max_y - edge_threshold
                 )
             };
-            
+
             let remote_count = REMOTE_SCREENS.read().unwrap().len();
-            
+
+            let auth_status = {
+                let security = CONNECTION_SECURITY.read().unwrap();
+                match (is_connected, security.encrypted, security.authenticated) {
+                    (false, _, _) => "Not connected".to_string(),
+                    (true, true, true) => "Encrypted + authenticated".to_string(),
+                    (true, true, false) => "Encrypted, awaiting auth".to_string(),
+                    (true, false, _) => "Unencrypted".to_string(),
+                }
+            };
+
             let mut debug = DEBUG_INFO.write().unwrap();
             debug.mouse_x = mx;
             debug.mouse_y = my;
             debug.screen_bounds = format!("x:[{},{}] y:[{},{}]", total_min_x, total_max_x, total_min_y, total_max_y);
             debug.edge_status = edge_status;
             debug.remote_screen_count = remote_count;
+            debug.auth_status = auth_status;
             debug.last_update = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs();
         }
-        
+
         // Skip if not connected
         if !is_connected {
             continue;
         }
-        
+
         // Skip if we're being controlled (remote has our mouse)
         if being_controlled {
             continue;
         }
-        
+
         // If we're controlling remote, capture mouse movement and send to remote
         if control_active {
             let edge_pos = *EDGE_LOCK_POS.read().unwrap();
             let (remote_x, remote_y) = *REMOTE_MOUSE_POS.read().unwrap();
-            
+
             // Calculate delta from edge position (mouse always gets reset to edge)
             // So delta = current position - edge position
             let raw_delta_x = mx - edge_pos.0;
             let raw_delta_y = my - edge_pos.1;
-            
+
             // Apply sensitivity multiplier for more responsive feel
             let sensitivity = 1.5;
             let delta_x = (raw_delta_x as f64 * sensitivity) as i32;
             let delta_y = (raw_delta_y as f64 * sensitivity) as i32;
-            
+
             // Only send if there's actual movement
             if raw_delta_x != 0 || raw_delta_y != 0 {
                 // Debug: show delta calculation
-                println!("üéØ Delta: raw({},{}) -> scaled({},{}) | edge({},{}) mouse({},{})", 
+                println!("🎯 Delta: raw({},{}) -> scaled({},{}) | edge({},{}) mouse({},{})",
                     raw_delta_x, raw_delta_y, delta_x, delta_y, edge_pos.0, edge_pos.1, mx, my);
-                
+
                 // Update remote mouse position with the delta
                 let new_remote_x = remote_x + delta_x;
                 let new_remote_y = remote_y + delta_y;
-                
+
                 // Get remote screen bounds
                 let remote_screens = REMOTE_SCREENS.read().unwrap().clone();
                 let remote_min_x = remote_screens.iter().map(|s| s.x).min().unwrap_or(0);
                 let remote_max_x = remote_screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
                 let remote_min_y = remote_screens.iter().map(|s| s.y).min().unwrap_or(0);
                 let remote_max_y = remote_screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-                
+
                 // Check if remote mouse would go past the "return" edge
                 let now = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -891,14 +1814,14 @@ pub async fn start_mouse_tracking() {
                     .as_millis() as u64;
                 let start_time = *CONTROL_START_TIME.read().unwrap();
                 let elapsed = now - start_time;
-                
+
                 // Get local screen info
                 let screens = crate::input::get_all_screens();
                 let total_min_x = screens.iter().map(|s| s.x).min().unwrap_or(0);
                 let total_max_x = screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
                 let total_min_y = screens.iter().map(|s| s.y).min().unwrap_or(0);
                 let total_max_y = screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-                
+
                 // Check for return to local (after cooldown)
                 let should_return = if elapsed > 500 {
                     // At right edge of local (went to Windows on the right) and remote going left past edge
@@ -911,12 +1834,14 @@ pub async fn start_mouse_tracking() {
                     }
                     else { false }
                 } else { false };
-                
+
                 if should_return {
-                    println!("üîô Returning control to local");
+                    println!("🔙 Returning control to local");
                     *CONTROL_ACTIVE.write().unwrap() = false;
-                    send_control_message("control_end", 0, 0).await;
-                    
+                    crate::input::stop_capture();
+                    crate::input::show_cursor();
+                    send_control_message(Message::control_end()).await;
+
                     // Move local mouse back into the screen
                     let return_x = if edge_pos.0 >= total_max_x - 20 { total_max_x - 50 } else { total_min_x + 50 };
                     crate::input::move_mouse(return_x, edge_pos.1);
@@ -924,18 +1849,18 @@ pub async fn start_mouse_tracking() {
                     // Clamp to remote screen bounds
                     let clamped_x = new_remote_x.clamp(remote_min_x, remote_max_x - 1);
                     let clamped_y = new_remote_y.clamp(remote_min_y, remote_max_y - 1);
-                    
+
                     // Store new remote position
                     *REMOTE_MOUSE_POS.write().unwrap() = (clamped_x, clamped_y);
-                    
+
                     // Send to remote
                     send_mouse_to_remote(clamped_x, clamped_y).await;
                 }
-                
+
                 // Always move local mouse back to edge position (keeps it hidden at edge)
                 crate::input::move_mouse(edge_pos.0, edge_pos.1);
             }
-            
+
             // Update last_pos to edge position (since we keep resetting there)
             last_pos = (edge_pos.0, edge_pos.1);
         } else {
@@ -948,61 +1873,103 @@ pub async fn start_mouse_tracking() {
     }
 }
 
+/// One crossing of the local machine's combined-desktop border, as detected
+/// by `detect_edge_transition`. `entry_ratio` is the cursor's normalized
+/// position (0.0-1.0) along the crossed edge, which `check_edge_transition`
+/// uses to map it onto the geometrically corresponding point on the remote
+/// machine's own (possibly differently sized) screen layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenTransition {
+    pub edge: &'static str,
+    pub entry_ratio: f64,
+}
+
+/// Pure geometry check, no global state: is the cursor within `threshold`
+/// pixels of `edge` of `local_bounds`? It fires as soon as the cursor enters
+/// that band; there is no dead-zone requiring travel past the edge.
+fn detect_edge_transition(
+    local_bounds: (i32, i32, i32, i32),
+    edge: &str,
+    mx: i32,
+    my: i32,
+    threshold: i32,
+) -> Option<ScreenTransition> {
+    let (min_x, max_x, min_y, max_y) = local_bounds;
+
+    let at_right_edge = mx >= max_x - threshold;
+    let at_left_edge = mx <= min_x + threshold;
+    let at_top_edge = my <= min_y + threshold;
+    let at_bottom_edge = my >= max_y - threshold;
+
+    let (edge, crossed) = match edge {
+        "right" => ("right", at_right_edge),
+        "left" => ("left", at_left_edge),
+        "top" => ("top", at_top_edge),
+        "bottom" => ("bottom", at_bottom_edge),
+        _ => ("right", at_right_edge), // default to right, matching the caller's existing fallback
+    };
+
+    if !crossed {
+        return None;
+    }
+
+    let width = (max_x - min_x) as f64;
+    let height = (max_y - min_y) as f64;
+    let entry_ratio = match edge {
+        "left" | "right" => if height > 0.0 { (my - min_y) as f64 / height } else { 0.5 },
+        _ => if width > 0.0 { (mx - min_x) as f64 / width } else { 0.5 },
+    };
+
+    Some(ScreenTransition { edge, entry_ratio })
+}
+
 async fn check_edge_transition(mx: i32, my: i32, threshold: i32) {
     let screens = crate::input::get_all_screens();
     if screens.is_empty() { return; }
-    
+
     // Find current screen bounds
     let total_min_x = screens.iter().map(|s| s.x).min().unwrap_or(0);
     let total_max_x = screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
     let total_min_y = screens.iter().map(|s| s.y).min().unwrap_or(0);
     let total_max_y = screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-    
+
     // Get remote screens
     let remote_screens = REMOTE_SCREENS.read().unwrap().clone();
     if remote_screens.is_empty() { return; }
-    
+
     // Calculate remote screen bounds
     let remote_min_x = remote_screens.iter().map(|s| s.x).min().unwrap_or(0);
     let remote_max_x = remote_screens.iter().map(|s| s.x + s.width).max().unwrap_or(1920);
     let remote_min_y = remote_screens.iter().map(|s| s.y).min().unwrap_or(0);
     let remote_max_y = remote_screens.iter().map(|s| s.y + s.height).max().unwrap_or(1080);
-    
+
     // Get configured edge direction (which edge leads to Windows)
     let remote_edge = REMOTE_EDGE.read().unwrap().clone();
-    
-    // Check edges - but only the configured one
-    let at_right_edge = mx >= total_max_x - threshold;
-    let at_left_edge = mx <= total_min_x + threshold;
-    let at_top_edge = my <= total_min_y + threshold;
-    let at_bottom_edge = my >= total_max_y - threshold;
-    
-    // Only trigger on the correct edge based on layout
-    let should_transition = match remote_edge.as_str() {
-        "right" => at_right_edge,
-        "left" => at_left_edge,
-        "top" => at_top_edge,
-        "bottom" => at_bottom_edge,
-        _ => at_right_edge  // Default to right
+
+    let transition = match detect_edge_transition(
+        (total_min_x, total_max_x, total_min_y, total_max_y),
+        &remote_edge,
+        mx,
+        my,
+        threshold,
+    ) {
+        Some(t) => t,
+        None => return,
     };
-    
-    if !should_transition {
-        return;
-    }
-    
-    println!("üéØ Edge detected ({})! Local bounds: x={}-{}, y={}-{}", remote_edge, total_min_x, total_max_x, total_min_y, total_max_y);
+
+    println!("🎯 Edge detected ({})! Local bounds: x={}-{}, y={}-{}", remote_edge, total_min_x, total_max_x, total_min_y, total_max_y);
     println!("   Remote bounds: x={}-{}, y={}-{}", remote_min_x, remote_max_x, remote_min_y, remote_max_y);
-    
-    // Calculate relative position (0.0 to 1.0) on local screens
-    let local_height = (total_max_y - total_min_y) as f64;
-    let local_width = (total_max_x - total_min_x) as f64;
-    let relative_y = if local_height > 0.0 { (my - total_min_y) as f64 / local_height } else { 0.5 };
-    let relative_x = if local_width > 0.0 { (mx - total_min_x) as f64 / local_width } else { 0.5 };
-    
+
+    // `entry_ratio` runs along Y for a left/right edge, X for a top/bottom
+    // one - normalize both local names to it so the remote-coordinate
+    // mapping below reads the same regardless of which edge fired.
+    let relative_y = transition.entry_ratio;
+    let relative_x = transition.entry_ratio;
+
     // Convert to remote coordinates based on which edge we're crossing
     let remote_height = (remote_max_y - remote_min_y) as f64;
     let remote_width = (remote_max_x - remote_min_x) as f64;
-    
+
     let (remote_x, remote_y) = match remote_edge.as_str() {
         "right" => {
             // Enter remote from left side, map Y proportionally
@@ -1030,9 +1997,9 @@ async fn check_edge_transition(mx: i32, my: i32, threshold: i32) {
             (remote_min_x + 10, mapped_y.clamp(remote_min_y, remote_max_y - 1))
         }
     };
-    
+
     println!("   Mapping local ({}, {}) -> remote ({}, {})", mx, my, remote_x, remote_y);
-    
+
     // Calculate edge lock position (where to keep local mouse pinned)
     let edge_x = match remote_edge.as_str() {
         "right" => total_max_x - 1,
@@ -1044,75 +2011,61 @@ async fn check_edge_transition(mx: i32, my: i32, threshold: i32) {
         "bottom" => total_max_y - 1,
         _ => my
     };
-    
+
     // Store edge lock position and initial remote mouse position
     *EDGE_LOCK_POS.write().unwrap() = (edge_x, edge_y);
     *REMOTE_MOUSE_POS.write().unwrap() = (remote_x, remote_y);
-    
+
     // Record start time for cooldown
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis() as u64;
     *CONTROL_START_TIME.write().unwrap() = now;
-    
+
     println!("   Edge lock at ({}, {}), remote starts at ({}, {})", edge_x, edge_y, remote_x, remote_y);
-    
+
     // Take control of remote
     *CONTROL_ACTIVE.write().unwrap() = true;
-    
+    crate::input::hide_cursor();
+    start_input_capture();
+
     // Send control_start message
-    send_control_message("control_start", remote_x, remote_y).await;
-    
+    send_control_message(Message::control_start(remote_x, remote_y)).await;
+
     // Move local mouse to edge position
     crate::input::move_mouse(edge_x, edge_y);
 }
 
 async fn send_mouse_to_remote(x: i32, y: i32) {
-    // Clone the client outside of async context to avoid Send issues
-    let client = {
-        ACTIVE_CLIENT.read().unwrap().clone()
-    };
-    
-    let writer = { WRITE_STREAM.read().unwrap().clone() };
-    
-    if let Some(writer) = writer {
-        let msg = Message::mouse_move(x, y);
-        let json = serde_json::to_string(&msg).unwrap_or_default() + "\n";
-        let mut stream = writer.lock().await;
-        let _ = stream.write_all(json.as_bytes()).await;
-    }
+    queue_batched_input(BatchedInput::MouseMove { x, y }).await;
 }
 
-async fn send_control_message(msg_type: &str, x: i32, y: i32) {
-    println!("üì§ Sending {} message at ({}, {})", msg_type, x, y);
-    
-    // Use the dedicated write stream (doesn't conflict with read loop)
-    let writer = { WRITE_STREAM.read().unwrap().clone() };
-    
+async fn send_control_message(msg: Message) {
+    println!("📤 Sending {:?}", msg);
+    send_to_peer(msg).await;
+}
+
+/// Send a message to the connected peer over the framed write stream.
+/// Bulk traffic (screen frames, layout sync, clipboard) prefers
+/// `BULK_WRITE_STREAM` when one's set up (QUIC), falling back to
+/// `WRITE_STREAM` otherwise - see `Message::is_bulk`.
+pub(crate) async fn send_to_peer(msg: Message) {
+    let writer = if msg.is_bulk() {
+        let bulk = { BULK_WRITE_STREAM.read().unwrap().clone() };
+        bulk.or_else(|| WRITE_STREAM.read().unwrap().clone())
+    } else {
+        WRITE_STREAM.read().unwrap().clone()
+    };
+
     if let Some(writer) = writer {
-        let msg = Message {
-            msg_type: msg_type.to_string(),
-            x: Some(x),
-            y: Some(y),
-            button: None, action: None, key_code: None,
-            text: None, name: None, version: None,
-            screens: None, computer_type: None, layout: None,
-        };
-        let json = serde_json::to_string(&msg).unwrap_or_default() + "\n";
-        println!("üì§ Sending JSON: {}", json.trim());
         let mut stream = writer.lock().await;
-        println!("üì§ Got write lock, sending...");
-        match stream.write_all(json.as_bytes()).await {
-            Ok(_) => {
-                println!("‚úÖ Message sent successfully");
-                // Flush to ensure it's sent immediately
-                let _ = stream.flush().await;
-            }
-            Err(e) => println!("‚ùå Failed to send message: {}", e),
+        match stream.send(msg).await {
+            Ok(_) => {}
+            Err(e) => println!("❌ Failed to send message: {}", e),
         }
     } else {
-        println!("‚ùå No write stream available!");
+        println!("❌ No write stream available!");
     }
 }
 
@@ -1120,54 +2073,52 @@ async fn send_control_message(msg_type: &str, x: i32, y: i32) {
 pub async fn send_key_to_remote(key_code: u32, action: &str) {
     let is_active = *CONTROL_ACTIVE.read().unwrap();
     if !is_active { return; }
-    
-    let writer = { WRITE_STREAM.read().unwrap().clone() };
-    
-    if let Some(writer) = writer {
-        let msg = Message::key_event(key_code, action);
-        let json = serde_json::to_string(&msg).unwrap_or_default() + "\n";
-        let mut stream = writer.lock().await;
-        let _ = stream.write_all(json.as_bytes()).await;
-    }
+    queue_batched_input(BatchedInput::Key { key_code, action: action.to_string() }).await;
 }
 
 /// Send mouse click to remote
 pub async fn send_click_to_remote(button: &str, action: &str) {
     let is_active = *CONTROL_ACTIVE.read().unwrap();
     if !is_active { return; }
-    
-    let writer = { WRITE_STREAM.read().unwrap().clone() };
-    
-    if let Some(writer) = writer {
-        let msg = Message::mouse_click(button, action);
-        let json = serde_json::to_string(&msg).unwrap_or_default() + "\n";
-        let mut stream = writer.lock().await;
-        let _ = stream.write_all(json.as_bytes()).await;
-    }
+    queue_batched_input(BatchedInput::MouseButton { button: button.to_string(), action: action.to_string() }).await;
+}
+
+/// Send a scroll delta to remote. `delta_x`/`delta_y` are fixed-point ticks
+/// (120 per notch); `precise` marks a trackpad/momentum event, see
+/// `input::TICKS_PER_NOTCH`.
+pub async fn send_scroll_to_remote(delta_x: i32, delta_y: i32, precise: bool) {
+    let is_active = *CONTROL_ACTIVE.read().unwrap();
+    if !is_active { return; }
+    queue_batched_input(BatchedInput::Scroll { delta_x, delta_y, precise }).await;
+}
+
+/// Send Unicode/IME text to remote, bypassing key codes entirely
+pub async fn send_text_to_remote(text: &str) {
+    let is_active = *CONTROL_ACTIVE.read().unwrap();
+    if !is_active { return; }
+    queue_batched_input(BatchedInput::TypeText(text.to_string())).await;
 }
 
 /// Release control back to local
 pub fn release_control() {
     *CONTROL_ACTIVE.write().unwrap() = false;
-    println!("üîì Control released back to local");
+    crate::input::stop_capture();
+    crate::input::show_cursor();
+    println!("🔓 Control released back to local");
 }
 
 /// Send layout sync to remote
 pub async fn send_layout_sync(layout_json: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("üìê Sending layout sync: {}", layout_json);
-    
+    println!("📐 Sending layout sync: {}", layout_json);
+
     let writer = { WRITE_STREAM.read().unwrap().clone() };
-    
+
     if let Some(writer) = writer {
-        let msg = Message::layout_sync(layout_json);
-        let json = serde_json::to_string(&msg)? + "\n";
         let mut stream = writer.lock().await;
-        stream.write_all(json.as_bytes()).await?;
-        stream.flush().await?;
-        println!("‚úÖ Layout sync sent successfully");
+        stream.send(Message::layout_sync(layout_json)).await?;
+        println!("✅ Layout sync sent successfully");
         Ok(())
     } else {
         Err("No write stream available".into())
     }
 }
-
@@ -0,0 +1,219 @@
+// Screen streaming - capture the active display, JPEG-encode only the
+// region that changed since the last frame, and ship it to the peer as a
+// `network::Message::ScreenFrame` alongside the existing input protocol.
+// Frames arriving from the peer are re-emitted to the frontend as a Tauri
+// event so a window can render them; this module doesn't know or care what
+// does the rendering.
+//
+// Not full-motion video: periodic JPEG keyframes, throttled to a
+// configurable FPS, with a path to a real codec (H.264) later if the
+// bandwidth/CPU tradeoff of JPEG keyframes turns out not to be enough.
+
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter};
+
+const FRAME_EVENT: &str = "remote-screen-frame";
+
+/// Stored globally the same way `network::WRITE_STREAM` is - set once during
+/// `run()`'s `.setup()` hook, read by whichever task needs to emit an event.
+static APP_HANDLE: Lazy<RwLock<Option<AppHandle>>> = Lazy::new(|| RwLock::new(None));
+
+static STREAMING: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+static TARGET_FPS: Lazy<RwLock<u32>> = Lazy::new(|| RwLock::new(10));
+
+/// Raw RGBA bytes of the last frame we captured, so we can diff against the
+/// next one and only send the sub-rectangle that actually changed.
+static LAST_FRAME: Lazy<RwLock<Option<image::RgbaImage>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn set_app_handle(handle: AppHandle) {
+    *APP_HANDLE.write().unwrap() = Some(handle);
+}
+
+pub fn is_streaming() -> bool {
+    *STREAMING.read().unwrap()
+}
+
+/// Start capturing the active display and sending frames to the peer.
+/// Spawns a background task; returns once that task is scheduled, not once
+/// streaming stops.
+pub async fn start_streaming(fps: u32) -> Result<(), String> {
+    *TARGET_FPS.write().unwrap() = fps.clamp(1, 60);
+    *LAST_FRAME.write().unwrap() = None;
+
+    if *STREAMING.read().unwrap() {
+        return Ok(());
+    }
+    *STREAMING.write().unwrap() = true;
+
+    tokio::spawn(async move {
+        capture_loop().await;
+    });
+
+    Ok(())
+}
+
+pub fn stop_streaming() {
+    *STREAMING.write().unwrap() = false;
+}
+
+async fn capture_loop() {
+    while *STREAMING.read().unwrap() {
+        let fps = (*TARGET_FPS.read().unwrap()).max(1);
+        let frame_delay = std::time::Duration::from_millis(1000 / fps as u64);
+
+        if let Some((frame, dirty)) = capture_dirty_region() {
+            let jpeg = match encode_region_jpeg(&frame, dirty) {
+                Ok(jpeg) => jpeg,
+                Err(e) => {
+                    eprintln!("⚠️ Failed to encode screen frame: {}", e);
+                    tokio::time::sleep(frame_delay).await;
+                    continue;
+                }
+            };
+
+            crate::network::send_to_peer(crate::network::Message::screen_frame(
+                frame.width(),
+                frame.height(),
+                dirty,
+                jpeg,
+            ))
+            .await;
+        }
+
+        tokio::time::sleep(frame_delay).await;
+    }
+}
+
+/// One captured frame's bounding box of changed pixels, relative to the full
+/// captured frame. `(x, y, width, height)`.
+pub type DirtyRegion = (u32, u32, u32, u32);
+
+/// Capture the active display and diff it against `LAST_FRAME`. Returns
+/// `None` if nothing changed (so the caller can skip sending entirely).
+fn capture_dirty_region() -> Option<(image::RgbaImage, DirtyRegion)> {
+    let frame = capture_active_display()?;
+
+    let dirty = {
+        let last = LAST_FRAME.read().unwrap();
+        match last.as_ref() {
+            Some(previous) if previous.dimensions() == frame.dimensions() => {
+                diff_bounds(previous, &frame)?
+            }
+            _ => (0, 0, frame.width(), frame.height()),
+        }
+    };
+
+    *LAST_FRAME.write().unwrap() = Some(frame.clone());
+    Some((frame, dirty))
+}
+
+/// Smallest rectangle containing every pixel that differs between `a` and
+/// `b`. `None` if the frames are identical (the common case at idle).
+fn diff_bounds(a: &image::RgbaImage, b: &image::RgbaImage) -> Option<DirtyRegion> {
+    let (width, height) = a.dimensions();
+    let (mut min_x, mut min_y) = (width, height);
+    let (mut max_x, mut max_y) = (0u32, 0u32);
+    let mut changed = false;
+
+    for y in 0..height {
+        for x in 0..width {
+            if a.get_pixel(x, y) != b.get_pixel(x, y) {
+                changed = true;
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+
+    Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+fn encode_region_jpeg(frame: &image::RgbaImage, region: DirtyRegion) -> Result<Vec<u8>, String> {
+    let (x, y, width, height) = region;
+    let cropped = image::imageops::crop_imm(frame, x, y, width, height).to_image();
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut std::io::Cursor::new(&mut jpeg), 70)
+        .encode(&cropped, width, height, image::ExtendedColorType::Rgba8)
+        .map_err(|e| e.to_string())?;
+    Ok(jpeg)
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn capture_active_display() -> Option<image::RgbaImage> {
+    use scrap::{Capturer, Display};
+
+    let display = Display::primary().ok()?;
+    let (width, height) = (display.width(), display.height());
+    let mut capturer = Capturer::new(display).ok()?;
+
+    // A capturer can return `WouldBlock` while the backend warms up; a
+    // handful of retries is enough in practice without blocking the loop.
+    for _ in 0..5 {
+        match capturer.frame() {
+            Ok(frame) => return bgra_to_rgba(&frame, width, height),
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn bgra_to_rgba(bgra: &[u8], width: usize, height: usize) -> Option<image::RgbaImage> {
+    let mut rgba = vec![0u8; width * height * 4];
+    for (src, dst) in bgra.chunks_exact(4).zip(rgba.chunks_exact_mut(4)) {
+        dst[0] = src[2];
+        dst[1] = src[1];
+        dst[2] = src[0];
+        dst[3] = 255;
+    }
+    image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_active_display() -> Option<image::RgbaImage> {
+    None
+}
+
+/// Re-emit a frame that arrived from the peer to the frontend, for a window
+/// to render. No-op if `set_app_handle` hasn't run yet (e.g. very early
+/// startup) or the frontend isn't listening.
+pub fn emit_remote_frame(frame_width: u32, frame_height: u32, region: DirtyRegion, jpeg: &[u8]) {
+    let Some(app) = APP_HANDLE.read().unwrap().clone() else {
+        return;
+    };
+
+    let (x, y, width, height) = region;
+    let payload = RemoteFramePayload {
+        frame_width,
+        frame_height,
+        x,
+        y,
+        width,
+        height,
+        jpeg_base64: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, jpeg),
+    };
+
+    if let Err(e) = app.emit(FRAME_EVENT, payload) {
+        eprintln!("⚠️ Failed to emit {}: {}", FRAME_EVENT, e);
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct RemoteFramePayload {
+    frame_width: u32,
+    frame_height: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    jpeg_base64: String,
+}
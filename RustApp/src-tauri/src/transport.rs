@@ -0,0 +1,153 @@
+// Transport abstraction - TCP (default, unchanged) or QUIC, selected via
+// `set_transport_kind`. Everything above the socket - `negotiate_version`,
+// `crypto::handshake`, `auth`'s challenge/response, `MessageCodec` framing -
+// is written against `BoxedReader`/`BoxedWriter` rather than a concrete
+// stream type, so switching transports doesn't touch any of that logic. See
+// `network::connect_to_server_quic`/`network::start_quic_server`.
+//
+// QUIC's payoff here is splitting the one TCP connection's traffic into two
+// independent bidirectional streams: `input` for latency-critical
+// mouse/key/scroll events, `bulk` for screen frames, layout sync, and
+// clipboard payloads. A big bulk transfer can then never head-of-line-block
+// a pointer move the way sharing one TCP connection would.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+pub type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+pub type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum TransportKind {
+    #[default]
+    Tcp,
+    Quic,
+}
+
+static TRANSPORT_KIND: Lazy<RwLock<TransportKind>> = Lazy::new(|| RwLock::new(TransportKind::default()));
+
+pub fn set_transport_kind(kind: TransportKind) {
+    *TRANSPORT_KIND.write().unwrap() = kind;
+}
+
+pub fn get_transport_kind() -> TransportKind {
+    *TRANSPORT_KIND.read().unwrap()
+}
+
+/// Glues a boxed reader and a boxed writer into a single `AsyncRead +
+/// AsyncWrite`, so functions generic over one duplex type (`negotiate_version`,
+/// `crypto::handshake`) work unmodified whether the underlying halves came
+/// from a split TCP socket or one half of a QUIC bidirectional stream.
+pub struct Duplex<'a> {
+    pub reader: &'a mut BoxedReader,
+    pub writer: &'a mut BoxedWriter,
+}
+
+impl AsyncRead for Duplex<'_> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.reader).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Duplex<'_> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut *self.writer).poll_write(cx, buf)
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.writer).poll_flush(cx)
+    }
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.writer).poll_shutdown(cx)
+    }
+}
+
+/// Port the QUIC transport listens/dials on - separate from `TCP_PORT`
+/// since a peer might (briefly, mid-rollout) run either transport.
+pub const QUIC_PORT: u16 = 52527;
+
+/// Build a client `quinn::Endpoint` that doesn't verify the server's TLS
+/// certificate. That's intentional: peer identity is already proven at the
+/// app layer by `crypto::handshake`'s X25519 exchange plus `auth`'s HMAC
+/// challenge, exactly as it is over plain TCP - QUIC's TLS here is for
+/// transport security (stream multiplexing, loss recovery, roaming), not
+/// for authenticating who's on the other end.
+pub fn client_endpoint() -> std::io::Result<quinn::Endpoint> {
+    let mut endpoint = quinn::Endpoint::client("0.0.0.0:0".parse().unwrap())?;
+    let crypto = rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(std::sync::Arc::new(NoVerify))
+        .with_no_client_auth();
+    let client_config = quinn::ClientConfig::new(std::sync::Arc::new(
+        quinn::crypto::rustls::QuicClientConfig::try_from(crypto)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+    ));
+    endpoint.set_default_client_config(client_config);
+    Ok(endpoint)
+}
+
+/// Build a QUIC server endpoint with a fresh, self-signed certificate - same
+/// rationale as `client_endpoint`: identity is proven at the app layer, not
+/// by this certificate, so there's nothing to gain from a CA-issued one.
+pub fn server_endpoint(port: u16) -> std::io::Result<quinn::Endpoint> {
+    let cert = rcgen::generate_simple_self_signed(vec!["macwincontrol.local".into()])
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+    let cert_der = rustls::pki_types::CertificateDer::from(cert.cert);
+    let key_der = rustls::pki_types::PrivateKeyDer::Pkcs8(rustls::pki_types::PrivatePkcs8KeyDer::from(
+        cert.signing_key.serialize_der(),
+    ));
+
+    let server_crypto = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der], key_der)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+    let server_config = quinn::ServerConfig::with_crypto(std::sync::Arc::new(
+        quinn::crypto::rustls::QuicServerConfig::try_from(server_crypto)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+    ));
+
+    quinn::Endpoint::server(server_config, format!("0.0.0.0:{}", port).parse().unwrap())
+}
+
+/// Accepts every certificate without checking it - see `client_endpoint`'s
+/// doc comment for why that's fine here.
+#[derive(Debug)]
+struct NoVerify;
+
+impl rustls::client::danger::ServerCertVerifier for NoVerify {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        rustls::crypto::ring::default_provider().signature_verification_algorithms.supported_schemes()
+    }
+}